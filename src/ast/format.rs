@@ -0,0 +1,352 @@
+use crate::{
+    symbols::VariableType,
+    token::{Comment, Token, TokenType},
+};
+
+use super::{Cond, Decl, Expr, Program, Stmt};
+
+const INDENT: &str = "    ";
+
+/// Regenerates the source text of a program with a canonical layout. Comments are written
+/// back next to the code that follows them, or at the end of the line they shared with it.
+#[derive(Debug, Clone, Default)]
+pub struct Formatter {
+    /// Writes each declared variable in its own declaration
+    pub split_declarations: bool,
+}
+
+impl Formatter {
+    pub fn new(split_declarations: bool) -> Self {
+        Formatter { split_declarations }
+    }
+
+    /// Formats a program, `comments` are the ones the lexer found in its source
+    pub fn format(&self, program: &Program, comments: &[Comment]) -> String {
+        let mut writer = Writer {
+            comments,
+            next: 0,
+            lines: Vec::new(),
+        };
+        writer.comments_while(0, |comment| comment.following == TokenType::Begin);
+        writer.lines.push(String::from("begin"));
+        let first_statement = program.statements.first().map(start);
+        for (index, declaration) in program.declarations.iter().enumerate() {
+            let following = program
+                .declarations
+                .get(index + 1)
+                .map(|next| next.names[0].offset)
+                .or(first_statement)
+                .unwrap_or(usize::MAX);
+            self.declaracion(declaration, following, &mut writer);
+        }
+        writer.ordenes(&program.statements, 1, usize::MAX);
+        writer.comments_while(1, |comment| comment.following != TokenType::EOF);
+        writer.lines.push(String::from("end"));
+        writer.comments_while(0, |_| true);
+        writer.lines.join("\n") + "\n"
+    }
+
+    fn declaracion(&self, declaration: &Decl, following: usize, writer: &mut Writer) {
+        let tipo = match declaration.variable_type {
+            VariableType::Entero => "entero",
+            VariableType::Real => "real",
+        };
+        let names: Vec<&str> = declaration
+            .names
+            .iter()
+            .map(|name| name.lexeme.as_str())
+            .collect();
+        let last = &declaration.names[declaration.names.len() - 1];
+        writer.comments_while(1, |comment| comment.offset < last.offset);
+        match self.split_declarations {
+            true => {
+                for name in names {
+                    writer.line(1, format!("{} {};", tipo, name));
+                }
+            }
+            false => writer.line(1, format!("{} {};", tipo, names.join(", "))),
+        }
+        writer.trailing(last, following);
+    }
+}
+
+/// Output lines together with the comments that are still to be written, which are taken
+/// in the order of the source
+struct Writer<'a> {
+    comments: &'a [Comment],
+    next: usize,
+    lines: Vec<String>,
+}
+
+impl Writer<'_> {
+    fn line(&mut self, depth: usize, text: String) {
+        self.lines.push(format!("{}{}", INDENT.repeat(depth), text));
+    }
+
+    /// Writes in their own lines the next comments while they are accepted
+    fn comments_while(&mut self, depth: usize, accepts: impl Fn(&Comment) -> bool) {
+        while let Some(comment) = self.comments.get(self.next) {
+            if !accepts(comment) {
+                break;
+            }
+            self.line(depth, comment.text.clone());
+            self.next += 1;
+        }
+    }
+
+    /// Appends to the last line the comments that followed `last` in its source line, as long
+    /// as they come before the `following` offset where the next code starts
+    fn trailing(&mut self, last: &Token, following: usize) {
+        while let Some(comment) = self.comments.get(self.next) {
+            if comment.line != last.line
+                || comment.offset >= following
+                || comment.text.contains('\n')
+            {
+                break;
+            }
+            if let Some(line) = self.lines.last_mut() {
+                line.push(' ');
+                line.push_str(&comment.text);
+            }
+            self.next += 1;
+        }
+    }
+
+    /// Writes a list of statements, `following` is the offset of the code after the list
+    fn ordenes(&mut self, statements: &[Stmt], depth: usize, following: usize) {
+        for (index, statement) in statements.iter().enumerate() {
+            let next = statements.get(index + 1).map(start).unwrap_or(following);
+            match statement {
+                Stmt::Assign { target, value } => {
+                    let last = last_token(value);
+                    self.comments_while(depth, |comment| comment.offset < last.offset);
+                    self.line(depth, format!("{} := {};", target.lexeme, expresion(value)));
+                    self.trailing(last, next);
+                }
+                Stmt::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    end,
+                } => {
+                    self.header(depth, "if", condition, then_branch, end);
+                    let then_following = else_branch
+                        .as_ref()
+                        .and_then(|branch| branch.first())
+                        .map(start)
+                        .unwrap_or(end.offset);
+                    self.ordenes(then_branch, depth + 1, then_following);
+                    if let Some(else_branch) = else_branch {
+                        self.comments_while(depth + 1, |comment| {
+                            comment.following == TokenType::Else
+                        });
+                        self.line(depth, String::from("else"));
+                        self.ordenes(else_branch, depth + 1, end.offset);
+                    }
+                    self.close(depth, "end;", end, next);
+                }
+                Stmt::While {
+                    condition,
+                    body,
+                    end,
+                } => {
+                    self.header(depth, "while", condition, body, end);
+                    self.ordenes(body, depth + 1, end.offset);
+                    self.close(depth, "endwhile;", end, next);
+                }
+            }
+        }
+    }
+
+    /// Line that opens an `if` or a `while`
+    fn header(
+        &mut self,
+        depth: usize,
+        keyword: &str,
+        condition: &Cond,
+        body: &[Stmt],
+        end: &Token,
+    ) {
+        let last = last_token_logica(condition);
+        self.comments_while(depth, |comment| comment.offset < last.offset);
+        self.line(depth, format!("{} ({})", keyword, logica(condition)));
+        self.trailing(last, body.first().map(start).unwrap_or(end.offset));
+    }
+
+    /// Comments left at the end of a block, then the keyword that closes it
+    fn close(&mut self, depth: usize, keyword: &str, end: &Token, following: usize) {
+        self.comments_while(depth + 1, |comment| comment.offset < end.offset);
+        self.line(depth, keyword.to_string());
+        self.trailing(end, following);
+    }
+}
+
+/// Offset where a statement starts, `if` and `while` are found through their condition
+fn start(statement: &Stmt) -> usize {
+    match statement {
+        Stmt::Assign { target, .. } => target.offset,
+        Stmt::If { condition, .. } | Stmt::While { condition, .. } => {
+            first_token_logica(condition).offset
+        }
+    }
+}
+
+fn first_token(expr: &Expr) -> &Token {
+    match expr {
+        Expr::Num(token) | Expr::Var(token) => token,
+        Expr::Bin { left, .. } => first_token(left),
+    }
+}
+
+fn last_token(expr: &Expr) -> &Token {
+    match expr {
+        Expr::Num(token) | Expr::Var(token) => token,
+        Expr::Bin { right, .. } => last_token(right),
+    }
+}
+
+fn first_token_logica(condition: &Cond) -> &Token {
+    match condition {
+        Cond::Or(operands) | Cond::And(operands) => first_token_logica(&operands[0]),
+        Cond::Not(negated) => first_token_logica(negated),
+        Cond::Compare { left, .. } => first_token(left),
+    }
+}
+
+fn last_token_logica(condition: &Cond) -> &Token {
+    match condition {
+        Cond::Or(operands) | Cond::And(operands) => {
+            last_token_logica(&operands[operands.len() - 1])
+        }
+        Cond::Not(negated) => last_token_logica(negated),
+        Cond::Compare { right, .. } => last_token(right),
+    }
+}
+
+fn group(text: String, needed: bool) -> String {
+    match needed {
+        true => format!("({})", text),
+        false => text,
+    }
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Bin { op, .. } if op.token_type == TokenType::OperadorAritA => 1,
+        Expr::Bin { .. } => 2,
+        Expr::Num(_) | Expr::Var(_) => 3,
+    }
+}
+
+/// Operators are left associative, so a right operand of the same precedence keeps its
+/// parentheses
+pub fn expresion(expr: &Expr) -> String {
+    match expr {
+        Expr::Num(token) | Expr::Var(token) => token.lexeme.clone(),
+        Expr::Bin { op, left, right } => {
+            let own = precedence(expr);
+            format!(
+                "{} {} {}",
+                group(expresion(left), precedence(left) < own),
+                op.lexeme,
+                group(expresion(right), precedence(right) <= own)
+            )
+        }
+    }
+}
+
+fn level(condition: &Cond) -> u8 {
+    match condition {
+        Cond::Or(_) => 1,
+        Cond::And(_) => 2,
+        Cond::Not(_) | Cond::Compare { .. } => 3,
+    }
+}
+
+/// Nested chains of the same operator keep their parentheses so the tree is not reshaped
+pub fn logica(condition: &Cond) -> String {
+    let chain = |operands: &[Cond], separator: &str| {
+        operands
+            .iter()
+            .map(|operand| group(logica(operand), level(operand) <= level(condition)))
+            .collect::<Vec<String>>()
+            .join(separator)
+    };
+    match condition {
+        Cond::Or(operands) => chain(operands, " or "),
+        Cond::And(operands) => chain(operands, " and "),
+        Cond::Not(negated) => format!("not {}", group(logica(negated), level(negated) < 3)),
+        Cond::Compare { left, op, right } => {
+            format!("{} {} {}", expresion(left), op.lexeme, expresion(right))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ast::lower::lower, sintactic::SintacticAnalyzer};
+
+    use super::*;
+
+    fn format(source: &str, split_declarations: bool) -> String {
+        let mut sintactic = SintacticAnalyzer::new(source);
+        let (tree, errors) = sintactic.analize_all();
+        assert!(errors.is_empty() && sintactic.lexic.errors.is_empty());
+        let program = lower(&tree).expect("the program should lower");
+        Formatter::new(split_declarations).format(&program, &sintactic.lexic.comments)
+    }
+
+    #[test]
+    fn lays_out_a_program() {
+        let source = "begin entero a,b; a:=(1+2)*3; while(a>0) a:=a-1; endwhile; end";
+        assert_eq!(
+            format(source, true),
+            "begin\n    entero a;\n    entero b;\n    a := (1 + 2) * 3;\n    while (a > 0)\n        a := a - 1;\n    endwhile;\nend\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments_where_they_were() {
+        let source = "// header
+begin
+    entero a; // trailing
+    { before }
+    a := 1;
+    if (a > 0) // header
+        a := 2;
+        // before else
+    else
+        a := 3;
+        // before end
+    end;
+    while (a < 3)
+        a := a + 1;
+        // before endwhile
+    endwhile;
+    // before the final end
+end
+// after the final end
+";
+        assert_eq!(format(source, false), source);
+    }
+
+    #[test]
+    fn moves_comments_inside_a_statement_above_it() {
+        let source = "begin entero a; a := 1 + { two } 2; // one\nend";
+        assert_eq!(
+            format(source, false),
+            "begin\n    entero a;\n    { two }\n    a := 1 + 2; // one\nend\n"
+        );
+    }
+
+    #[test]
+    fn trailing_comments_stay_with_the_last_statement_of_their_line() {
+        let source = "begin entero a; a := 1; a := 2; // two\nend";
+        let formatted = format(source, false);
+        assert_eq!(
+            formatted,
+            "begin\n    entero a;\n    a := 1;\n    a := 2; // two\nend\n"
+        );
+        assert_eq!(format(&formatted, false), formatted);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    production::{Production, ProductionItem, ProductionType},
+    semantic::error::SemanticError,
+    symbols::VariableType,
+    token::{Token, TokenType},
+};
+
+use super::{Cond, Decl, Expr, Program, Stmt};
+
+pub type LowerResult<T> = Result<T, SemanticError>;
+
+// Children are looked up by their type instead of their position, so adding a token to a
+// production does not change how it is lowered
+
+fn nodes(prod: &Production, production_type: ProductionType) -> impl Iterator<Item = &Production> {
+    prod.items.iter().filter_map(move |item| match item {
+        ProductionItem::Production(child) if child.production_type == production_type => {
+            Some(child)
+        }
+        _ => None,
+    })
+}
+
+fn node(prod: &Production, production_type: ProductionType) -> LowerResult<&Production> {
+    nodes(prod, production_type)
+        .next()
+        .ok_or_else(|| SemanticError::from_format(prod.clone()))
+}
+
+fn leaf(prod: &Production, accepts: fn(&TokenType) -> bool) -> Option<&Token> {
+    prod.items.iter().find_map(|item| match item {
+        ProductionItem::Leaf(token) if accepts(&token.token_type) => Some(token),
+        _ => None,
+    })
+}
+
+fn required_leaf(prod: &Production, accepts: fn(&TokenType) -> bool) -> LowerResult<&Token> {
+    leaf(prod, accepts).ok_or_else(|| SemanticError::from_format(prod.clone()))
+}
+
+/// Items of a list such as `Ordenes`, where each `rest` production holds more items and the
+/// next `rest`
+fn list(prod: &Production, item: ProductionType, rest: ProductionType) -> Vec<&Production> {
+    let mut items: Vec<&Production> = nodes(prod, item.clone()).collect();
+    for next in nodes(prod, rest.clone()) {
+        items.extend(list(next, item.clone(), rest.clone()));
+    }
+    items
+}
+
+/// First operand and the operator/operand pairs of a left-factored chain such as
+/// `termino rest_expr`
+fn chain(
+    prod: &Production,
+    operand: ProductionType,
+    rest: ProductionType,
+) -> LowerResult<(&Production, Vec<(&Token, &Production)>)> {
+    let first = node(prod, operand.clone())?;
+    let mut pairs = Vec::new();
+    let mut current = node(prod, rest.clone())?;
+    while !current.items.is_empty() {
+        let op = required_leaf(current, |_| true)?;
+        pairs.push((op, node(current, operand.clone())?));
+        current = node(current, rest.clone())?;
+    }
+    Ok((first, pairs))
+}
+
+fn declaracion(prod: &Production) -> LowerResult<Decl> {
+    let tipo = required_leaf(prod, |token_type| *token_type == TokenType::Tipo)?;
+    let variable_type = match tipo.lexeme.as_str() {
+        "entero" => VariableType::Entero,
+        _ => VariableType::Real,
+    };
+    let mut names = Vec::new();
+    let mut lista = Some(node(prod, ProductionType::ListaVariables)?);
+    while let Some(current) = lista {
+        names.push(required_leaf(current, |token_type| *token_type == TokenType::Id)?.clone());
+        let sig = node(current, ProductionType::SigListaVariables)?;
+        lista = nodes(sig, ProductionType::ListaVariables).next();
+    }
+    Ok(Decl {
+        variable_type,
+        names,
+    })
+}
+
+fn numeros(prod: &Production) -> LowerResult<Expr> {
+    let token = required_leaf(prod, |token_type| {
+        matches!(token_type, TokenType::Entero | TokenType::Real)
+    })?;
+    Ok(Expr::Num(token.clone()))
+}
+
+fn factor(prod: &Production) -> LowerResult<Expr> {
+    if let Ok(expresion) = node(prod, ProductionType::ExpresionArit) {
+        return expresion_arit(expresion);
+    }
+    let operador = node(prod, ProductionType::Operador)?;
+    match leaf(operador, |token_type| *token_type == TokenType::Id) {
+        Some(id) => Ok(Expr::Var(id.clone())),
+        None => numeros(node(operador, ProductionType::Numeros)?),
+    }
+}
+
+fn binary(
+    prod: &Production,
+    operand: ProductionType,
+    rest: ProductionType,
+    lower: fn(&Production) -> LowerResult<Expr>,
+) -> LowerResult<Expr> {
+    let (first, pairs) = chain(prod, operand, rest)?;
+    let mut expr = lower(first)?;
+    for (op, operand) in pairs {
+        expr = Expr::Bin {
+            op: op.clone(),
+            left: Box::new(expr),
+            right: Box::new(lower(operand)?),
+        };
+    }
+    Ok(expr)
+}
+
+fn termino(prod: &Production) -> LowerResult<Expr> {
+    binary(
+        prod,
+        ProductionType::Factor,
+        ProductionType::RestTerm,
+        factor,
+    )
+}
+
+pub fn expresion_arit(prod: &Production) -> LowerResult<Expr> {
+    binary(prod, ProductionType::Term, ProductionType::RestExp, termino)
+}
+
+fn comparacion(prod: &Production) -> LowerResult<Cond> {
+    let mut sides = nodes(prod, ProductionType::ExpresionArit);
+    let (left, right) = match (sides.next(), sides.next()) {
+        (Some(left), Some(right)) => (left, right),
+        _ => return Err(SemanticError::from_format(prod.clone())),
+    };
+    let op = required_leaf(prod, |token_type| {
+        *token_type == TokenType::OperadorCondicion
+    })?;
+    Ok(Cond::Compare {
+        left: expresion_arit(left)?,
+        op: op.clone(),
+        right: expresion_arit(right)?,
+    })
+}
+
+fn factor_logico(prod: &Production) -> LowerResult<Cond> {
+    if leaf(prod, |token_type| *token_type == TokenType::Not).is_some() {
+        let negated = factor_logico(node(prod, ProductionType::FactorLogico)?)?;
+        return Ok(Cond::Not(Box::new(negated)));
+    }
+    if let Ok(group) = node(prod, ProductionType::ExpresionLogica) {
+        return expresion_logica(group);
+    }
+    comparacion(node(prod, ProductionType::Comparacion)?)
+}
+
+fn logical(
+    prod: &Production,
+    operand: ProductionType,
+    rest: ProductionType,
+    lower: fn(&Production) -> LowerResult<Cond>,
+    combine: fn(Vec<Cond>) -> Cond,
+) -> LowerResult<Cond> {
+    let (first, pairs) = chain(prod, operand, rest)?;
+    if pairs.is_empty() {
+        return lower(first);
+    }
+    let mut operands = vec![lower(first)?];
+    for (_, operand) in pairs {
+        operands.push(lower(operand)?);
+    }
+    Ok(combine(operands))
+}
+
+fn termino_logico(prod: &Production) -> LowerResult<Cond> {
+    logical(
+        prod,
+        ProductionType::FactorLogico,
+        ProductionType::RestTerminoLogico,
+        factor_logico,
+        Cond::And,
+    )
+}
+
+pub fn expresion_logica(prod: &Production) -> LowerResult<Cond> {
+    logical(
+        prod,
+        ProductionType::TerminoLogico,
+        ProductionType::RestLogica,
+        termino_logico,
+        Cond::Or,
+    )
+}
+
+fn asignar(prod: &Production) -> LowerResult<Stmt> {
+    let target = required_leaf(prod, |token_type| *token_type == TokenType::Id)?;
+    Ok(Stmt::Assign {
+        target: target.clone(),
+        value: expresion_arit(node(prod, ProductionType::ExpresionArit)?)?,
+    })
+}
+
+fn condicion(prod: &Production) -> LowerResult<Stmt> {
+    let sig_condicion = node(prod, ProductionType::SigCondicion)?;
+    let else_branch = match nodes(sig_condicion, ProductionType::Ordenes).next() {
+        Some(ordenes) => Some(ordenes_list(ordenes)?),
+        None => None,
+    };
+    Ok(Stmt::If {
+        condition: expresion_logica(node(prod, ProductionType::ExpresionLogica)?)?,
+        then_branch: ordenes_list(node(prod, ProductionType::Ordenes)?)?,
+        else_branch,
+        end: required_leaf(sig_condicion, |token_type| *token_type == TokenType::End)?.clone(),
+    })
+}
+
+fn bucle_while(prod: &Production) -> LowerResult<Stmt> {
+    Ok(Stmt::While {
+        condition: expresion_logica(node(prod, ProductionType::ExpresionLogica)?)?,
+        body: ordenes_list(node(prod, ProductionType::Ordenes)?)?,
+        end: required_leaf(prod, |token_type| *token_type == TokenType::Endwhile)?.clone(),
+    })
+}
+
+fn orden(prod: &Production) -> LowerResult<Stmt> {
+    let content = match prod.items.first() {
+        Some(ProductionItem::Production(content)) => content,
+        Some(ProductionItem::Leaf(token)) => {
+            return Err(SemanticError::from_unexpected(token.clone()))
+        }
+        None => return Err(SemanticError::from_format(prod.clone())),
+    };
+    match content.production_type {
+        ProductionType::Condicion => condicion(content),
+        ProductionType::BucleWhile => bucle_while(content),
+        ProductionType::Asignar => asignar(content),
+        _ => Err(SemanticError::from_format(content.clone())),
+    }
+}
+
+fn ordenes_list(prod: &Production) -> LowerResult<Vec<Stmt>> {
+    list(prod, ProductionType::Orden, ProductionType::SigOrdenes)
+        .into_iter()
+        .map(orden)
+        .collect()
+}
+
+/// Builds the typed tree of a program parsed without errors
+pub fn lower(tree: &Production) -> LowerResult<Program> {
+    let declarations = list(
+        node(tree, ProductionType::Declaraciones)?,
+        ProductionType::Declaracion,
+        ProductionType::SigDeclaraciones,
+    )
+    .into_iter()
+    .map(declaracion)
+    .collect::<LowerResult<Vec<Decl>>>()?;
+    Ok(Program {
+        declarations,
+        statements: ordenes_list(node(tree, ProductionType::Ordenes)?)?,
+    })
+}
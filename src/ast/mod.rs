@@ -0,0 +1,60 @@
+pub mod format;
+pub mod lower;
+
+use crate::{symbols::VariableType, token::Token};
+
+/// Program with its declarations and statements, independent of how the grammar nests them
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub declarations: Vec<Decl>,
+    pub statements: Vec<Stmt>,
+}
+
+/// Declaration of one or more variables of the same type
+#[derive(Debug, Clone)]
+pub struct Decl {
+    pub variable_type: VariableType,
+    pub names: Vec<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    Assign {
+        target: Token,
+        value: Expr,
+    },
+    If {
+        condition: Cond,
+        then_branch: Vec<Stmt>,
+        else_branch: Option<Vec<Stmt>>,
+        /// Closing `end`, where the comments at the end of the statement are written back
+        end: Token,
+    },
+    While {
+        condition: Cond,
+        body: Vec<Stmt>,
+        /// Closing `endwhile`
+        end: Token,
+    },
+}
+
+/// Arithmetic expression, binary operations are already grouped left to right
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Num(Token),
+    Var(Token),
+    Bin {
+        op: Token,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+/// Condition of an `if` or `while`, `And` and `Or` hold the operands of a whole chain
+#[derive(Debug, Clone)]
+pub enum Cond {
+    Compare { left: Expr, op: Token, right: Expr },
+    And(Vec<Cond>),
+    Or(Vec<Cond>),
+    Not(Box<Cond>),
+}
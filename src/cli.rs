@@ -0,0 +1,266 @@
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use crate::{
+    lexic::DEFAULT_TAB_WIDTH,
+    messages::{format, text, Locale},
+};
+
+/// The program ran and produced its output
+pub const EXIT_OK: u8 = 0;
+/// The program has lexical, syntax or semantic errors, or `fmt --check` found it unformatted
+pub const EXIT_DIAGNOSTICS: u8 = 1;
+/// Bad command line arguments or the input could not be read
+pub const EXIT_USAGE: u8 = 2;
+/// The program compiled but failed while running
+pub const EXIT_RUNTIME: u8 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Tokens,
+    Parse,
+    Ir,
+    Run,
+    Check,
+    Fmt,
+    Help,
+}
+
+impl Command {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tokens" => Some(Command::Tokens),
+            "parse" => Some(Command::Parse),
+            "ir" => Some(Command::Ir),
+            "run" => Some(Command::Run),
+            "check" => Some(Command::Check),
+            "fmt" => Some(Command::Fmt),
+            "help" => Some(Command::Help),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Tokens => "tokens",
+            Command::Parse => "parse",
+            Command::Ir => "ir",
+            Command::Run => "run",
+            Command::Check => "check",
+            Command::Fmt => "fmt",
+            Command::Help => "help",
+        }
+    }
+}
+
+/// Command an option belongs to, `None` for the options every command accepts
+fn option_command(option: &str) -> Option<Command> {
+    match option {
+        "--dot" => Some(Command::Parse),
+        "--dag-dot" | "--cfg-dot" => Some(Command::Ir),
+        "--check" | "--split-decls" => Some(Command::Fmt),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub command: Command,
+    pub path: Option<String>,
+    pub dot: bool,
+    pub dag_dot: bool,
+    pub cfg_dot: bool,
+    pub check: bool,
+    pub split_declarations: bool,
+    pub color: bool,
+    pub json: bool,
+    /// Columns a tab advances to, used both for positions and for rendering them
+    pub tab_width: usize,
+    pub locale: Locale,
+}
+
+impl Options {
+    /// Parses the arguments that follow the program name. The command can be given anywhere
+    /// or omitted, in which case the input is translated to three-address code. Options that
+    /// belong to another command are rejected.
+    pub fn parse(arguments: &[String]) -> Result<Self, String> {
+        let locale = Options::requested_locale(arguments);
+        let mut options = Options {
+            command: Command::Ir,
+            path: None,
+            dot: false,
+            dag_dot: false,
+            cfg_dot: false,
+            check: false,
+            split_declarations: false,
+            color: false,
+            json: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+            locale,
+        };
+        let mut command = None;
+        let mut help = false;
+        let mut specific: Vec<&str> = Vec::new();
+        let mut arguments = arguments.iter();
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--dot" => options.dot = true,
+                "--dag-dot" => options.dag_dot = true,
+                "--cfg-dot" => options.cfg_dot = true,
+                "--check" => options.check = true,
+                "--split-decls" => options.split_declarations = true,
+                "--color" => options.color = true,
+                "--json" => options.json = true,
+                "--lang" => {
+                    let name = arguments
+                        .next()
+                        .ok_or_else(|| text("cli-missing-language", locale).to_string())?;
+                    options.locale = Locale::from_name(name)
+                        .ok_or_else(|| format("cli-unknown-language", locale, &[name]))?;
+                }
+                "--tab-width" => {
+                    let width = arguments
+                        .next()
+                        .ok_or_else(|| text("cli-missing-tab-width", locale).to_string())?;
+                    options.tab_width = width
+                        .parse()
+                        .ok()
+                        .filter(|width| *width > 0)
+                        .ok_or_else(|| format("cli-invalid-tab-width", locale, &[width]))?;
+                }
+                "-h" | "--help" => help = true,
+                "-" if options.path.is_none() => options.path = Some(argument.clone()),
+                flag if flag.starts_with('-') => {
+                    return Err(format("cli-unknown-option", locale, &[&flag]))
+                }
+                name if command.is_none() && Command::from_name(name).is_some() => {
+                    command = Command::from_name(name)
+                }
+                path if options.path.is_none() => options.path = Some(path.to_string()),
+                extra => return Err(format("cli-unexpected-argument", locale, &[&extra])),
+            }
+            if option_command(argument).is_some() {
+                specific.push(argument);
+            }
+        }
+        options.command = command.unwrap_or(Command::Ir);
+        if help {
+            options.command = Command::Help;
+            return Ok(options);
+        }
+        if let Some(option) = specific
+            .iter()
+            .find(|option| option_command(option).as_ref() != Some(&options.command))
+        {
+            let command = options.command.name();
+            return Err(format("cli-option-command", locale, &[option, &command]));
+        }
+        if options.dag_dot && options.cfg_dot {
+            return Err(format(
+                "cli-conflicting-options",
+                locale,
+                &[&"--dag-dot", &"--cfg-dot"],
+            ));
+        }
+        Ok(options)
+    }
+
+    /// Language asked for with `--lang`, known before parsing so that the errors about the
+    /// other arguments are already in that language
+    pub fn requested_locale(arguments: &[String]) -> Locale {
+        arguments
+            .windows(2)
+            .find(|pair| pair[0] == "--lang")
+            .and_then(|pair| Locale::from_name(&pair[1]))
+            .unwrap_or_default()
+    }
+
+    /// Reads the program from the given path, or from the standard input when there is none
+    pub fn read_source(&self) -> io::Result<String> {
+        match self.path.as_deref() {
+            None | Some("-") => {
+                let mut source = String::new();
+                io::stdin().read_to_string(&mut source)?;
+                Ok(source)
+            }
+            Some(path) => fs::read_to_string(path),
+        }
+    }
+
+    /// Name shown for the input in messages
+    pub fn source_name(&self) -> &str {
+        match self.path.as_deref() {
+            None | Some("-") => "<stdin>",
+            Some(path) => path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &str) -> Result<Options, String> {
+        let arguments: Vec<String> = arguments.split_whitespace().map(String::from).collect();
+        Options::parse(&arguments)
+    }
+
+    #[test]
+    fn the_command_can_come_after_the_options() {
+        let options = parse("--lang en run test").expect("the arguments are valid");
+        assert_eq!(options.command, Command::Run);
+        assert_eq!(options.path.as_deref(), Some("test"));
+        assert_eq!(options.locale, Locale::En);
+        let options = parse("test fmt --check").expect("the arguments are valid");
+        assert_eq!(options.command, Command::Fmt);
+        assert_eq!(options.path.as_deref(), Some("test"));
+        assert_eq!(
+            parse("test").map(|options| options.command),
+            Ok(Command::Ir)
+        );
+    }
+
+    #[test]
+    fn rejects_options_of_another_command() {
+        assert_eq!(
+            parse("tokens --dot test").err(),
+            Some(String::from(
+                "La opción '--dot' no se puede usar con el comando 'tokens'"
+            ))
+        );
+        assert!(parse("run --check test").is_err());
+        assert!(parse("--split-decls test").is_err());
+        assert!(parse("parse --dot test").is_ok());
+        assert!(parse("fmt --check --split-decls --json --color test").is_ok());
+    }
+
+    #[test]
+    fn rejects_both_graphs_of_ir_at_once() {
+        assert_eq!(
+            parse("ir --dag-dot --cfg-dot --lang en test").err(),
+            Some(String::from(
+                "The options '--dag-dot' and '--cfg-dot' cannot be used together"
+            ))
+        );
+    }
+
+    #[test]
+    fn help_ignores_the_other_arguments() {
+        let options = parse("run --dot --help").expect("help is always accepted");
+        assert_eq!(options.command, Command::Help);
+    }
+
+    #[test]
+    fn reads_a_positive_tab_width() {
+        assert_eq!(parse("test").map(|options| options.tab_width), Ok(4));
+        assert_eq!(
+            parse("--tab-width 8 test").map(|options| options.tab_width),
+            Ok(8)
+        );
+        assert!(parse("--tab-width 0 test").is_err());
+        assert!(parse("--tab-width wide test").is_err());
+        assert!(parse("test --tab-width").is_err());
+    }
+}
@@ -0,0 +1,481 @@
+use crate::{
+    interpreter::error::InterpreterError,
+    lexic::error::LexicError,
+    messages::{format, text, Locale},
+    semantic::{
+        error::{SemanticError, SemanticErrorType},
+        warning::SemanticWarning,
+    },
+    sintactic::error::SintacticError,
+    token::{Token, TokenType},
+};
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self, locale: Locale) -> &'static str {
+        match self {
+            Severity::Error => text("severity-error", locale),
+            Severity::Warning => text("severity-warning", locale),
+        }
+    }
+
+    /// Name used in the JSON output
+    pub fn key(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+}
+
+/// Part of a single source line, columns start at 1 and `end_col` is exclusive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub line: u32,
+    pub col: u32,
+    pub end_col: u32,
+}
+
+impl Span {
+    pub fn from_token(token: &Token) -> Self {
+        let length = match token.token_type {
+            TokenType::EOF => 1,
+            _ => token.lexeme.chars().count().max(1) as u32,
+        };
+        Span {
+            line: token.line,
+            col: token.col,
+            end_col: token.col + length,
+        }
+    }
+}
+
+/// A span with an optional note shown next to its underline
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(token: &Token, message: &str) -> Self {
+        Label {
+            span: Span::from_token(token),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub primary: Option<Label>,
+    pub secondary: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        code: &'static str,
+        message: &str,
+        primary: Option<Label>,
+    ) -> Self {
+        Diagnostic {
+            severity,
+            code,
+            message: message.trim_end().to_string(),
+            primary,
+            secondary: Vec::new(),
+        }
+    }
+
+    pub fn from_lexic(error: &LexicError, locale: Locale) -> Self {
+        Diagnostic::new(
+            Severity::Error,
+            error.code(),
+            &error.message(locale),
+            Some(Label::new(&error.token, "")),
+        )
+    }
+
+    pub fn from_sintactic(error: &SintacticError, locale: Locale) -> Self {
+        let expected = format(
+            "label-expected",
+            locale,
+            &[&error.expected().to_str(locale)],
+        );
+        Diagnostic::new(
+            Severity::Error,
+            error.code(),
+            &error.message(locale),
+            Some(Label::new(error.token(), &expected)),
+        )
+    }
+
+    /// Error of a program that compiled but failed while running, it has no source span
+    pub fn from_runtime(error: &InterpreterError, locale: Locale) -> Self {
+        Diagnostic::new(Severity::Error, error.code(), &error.message(locale), None)
+    }
+
+    pub fn from_error(error: &SemanticError, locale: Locale) -> Self {
+        match error.error_type() {
+            SemanticErrorType::Lexic(lexic) => return Diagnostic::from_lexic(lexic, locale),
+            SemanticErrorType::Sintactic(sintactic) => {
+                return Diagnostic::from_sintactic(sintactic, locale)
+            }
+            _ => {}
+        }
+        let message = error.message(locale);
+        let primary = error.token().map(|token| Label::new(token, ""));
+        let mut diagnostic = Diagnostic::new(Severity::Error, error.code(), &message, primary);
+        if let SemanticErrorType::Redeclared(_, previous) = error.error_type() {
+            diagnostic
+                .secondary
+                .push(Label::new(previous, text("label-declared-here", locale)));
+        }
+        diagnostic
+    }
+
+    pub fn from_warning(warning: &SemanticWarning, locale: Locale) -> Self {
+        Diagnostic::new(
+            Severity::Warning,
+            warning.code(),
+            &warning.message(locale),
+            Some(Label::new(
+                warning.token(),
+                text("label-declared-here", locale),
+            )),
+        )
+    }
+
+    /// Serializes the diagnostic as a JSON object, positions are `null` when it has no span
+    pub fn to_json(&self, file: &str) -> String {
+        let span = self.primary.as_ref().map(|label| &label.span);
+        let labels: Vec<String> = self
+            .secondary
+            .iter()
+            .map(|label| {
+                format!(
+                    "{{{},\"message\":\"{}\"}}",
+                    span_json(Some(&label.span)),
+                    escape(&label.message)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"severity\":\"{}\",\"code\":\"{}\",\"message\":\"{}\",\"file\":\"{}\",{},\"labels\":[{}]}}",
+            self.severity.key(),
+            self.code,
+            escape(&self.message),
+            escape(file),
+            span_json(span),
+            labels.join(",")
+        )
+    }
+}
+
+/// Serializes every diagnostic into a single JSON array
+pub fn to_json(diagnostics: &[Diagnostic], file: &str) -> String {
+    let objects: Vec<String> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_json(file))
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+fn span_json(span: Option<&Span>) -> String {
+    match span {
+        Some(span) => format!(
+            "\"start_line\":{},\"start_column\":{},\"end_line\":{},\"end_column\":{}",
+            span.line, span.col, span.line, span.end_col
+        ),
+        None => String::from(
+            "\"start_line\":null,\"start_column\":null,\"end_line\":null,\"end_column\":null",
+        ),
+    }
+}
+
+/// Escapes a string so it can be written inside a quoted JSON string
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            control if control.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", control as u32))
+            }
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Renders diagnostics with the offending source line and a `^~~~` underline below the span
+pub struct Renderer<'a> {
+    pub source: &'a str,
+    pub file: String,
+    pub color: bool,
+    pub tab_width: usize,
+    pub locale: Locale,
+    /// Lines of the source, split once so each diagnostic finds its line directly
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    /// `tab_width` has to be the one the positions were computed with, so the underlines
+    /// start below the right column
+    pub fn new(source: &'a str, file: &str, color: bool, tab_width: usize, locale: Locale) -> Self {
+        Renderer {
+            source,
+            file: file.to_string(),
+            color,
+            tab_width: tab_width.max(1),
+            locale,
+            lines: source.lines().collect(),
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        match self.color {
+            true => format!("{}{}{}", color, text, RESET),
+            false => text.to_string(),
+        }
+    }
+
+    /// Source line with tabs expanded so the columns match the lexer ones
+    fn line(&self, line: u32) -> Option<String> {
+        let text = self.lines.get(line.checked_sub(1)? as usize)?;
+        let mut expanded = String::new();
+        let mut col = 1;
+        for character in text.chars() {
+            match character {
+                '\t' => {
+                    let width = self.tab_width - (col - 1) % self.tab_width;
+                    expanded.push_str(&" ".repeat(width));
+                    col += width;
+                }
+                '\r' => {}
+                // Other control characters, such as a NUL byte, are drawn with their symbol
+                // so they keep their column without being written to the terminal
+                control if control.is_control() => {
+                    let symbol = match control as u32 {
+                        code @ 0..=0x1f => char::from_u32(0x2400 + code),
+                        0x7f => Some('\u{2421}'),
+                        _ => None,
+                    };
+                    expanded.push(symbol.unwrap_or(char::REPLACEMENT_CHARACTER));
+                    col += 1;
+                }
+                _ => {
+                    expanded.push(character);
+                    col += 1;
+                }
+            }
+        }
+        Some(expanded)
+    }
+
+    fn underline(&self, label: &Label, primary: bool, severity: Severity) -> String {
+        let start = label.span.col.max(1) as usize - 1;
+        let length = (label.span.end_col.saturating_sub(label.span.col)).max(1) as usize;
+        let marks = match primary {
+            true => format!("^{}", "~".repeat(length - 1)),
+            false => "-".repeat(length),
+        };
+        let color = if primary { severity.color() } else { BLUE };
+        let mut underline = format!("{}{}", " ".repeat(start), self.paint(&marks, color));
+        if !label.message.is_empty() {
+            underline.push(' ');
+            underline.push_str(&self.paint(&label.message, color));
+        }
+        underline
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut lines = vec![format!(
+            "{}{}",
+            self.paint(
+                &format!(
+                    "{}[{}]:",
+                    diagnostic.severity.as_str(self.locale),
+                    diagnostic.code
+                ),
+                diagnostic.severity.color()
+            ),
+            self.paint(&format!(" {}", diagnostic.message), BOLD)
+        )];
+        let mut labels: Vec<(&Label, bool)> = diagnostic
+            .primary
+            .iter()
+            .map(|label| (label, true))
+            .chain(diagnostic.secondary.iter().map(|label| (label, false)))
+            .collect();
+        let primary = match labels.first() {
+            Some((label, _)) => label.span.clone(),
+            None => return lines.join("\n"),
+        };
+        labels.sort_by_key(|(label, _)| label.span.line);
+        let gutter = labels
+            .iter()
+            .map(|(label, _)| label.span.line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let bar = self.paint("|", BLUE);
+        lines.push(format!(
+            "{}{} {}:{}:{}",
+            " ".repeat(gutter),
+            self.paint("-->", BLUE),
+            self.file,
+            primary.line,
+            primary.col
+        ));
+        lines.push(format!("{} {}", " ".repeat(gutter), bar));
+        let mut previous_line = None;
+        for (label, is_primary) in labels {
+            if previous_line != Some(label.span.line) {
+                let text = self.line(label.span.line).unwrap_or_default();
+                let number = format!("{:>width$}", label.span.line, width = gutter);
+                lines.push(format!("{} {} {}", self.paint(&number, BLUE), bar, text));
+                previous_line = Some(label.span.line);
+            }
+            lines.push(format!(
+                "{} {} {}",
+                " ".repeat(gutter),
+                bar,
+                self.underline(label, is_primary, diagnostic.severity)
+            ));
+        }
+        lines
+            .into_iter()
+            .map(|line| line.trim_end().to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(line: u32, col: u32, end_col: u32) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Error,
+            "E0200",
+            "variable no declarada",
+            Some(Label {
+                span: Span { line, col, end_col },
+                message: String::new(),
+            }),
+        )
+    }
+
+    #[test]
+    fn tabs_are_expanded_with_the_width_of_the_positions() {
+        let source = "begin\n\tz := 1;\nend";
+        let rendered = Renderer::new(source, "f", false, 8, Locale::Es).render(&error_at(2, 9, 10));
+        assert_eq!(
+            rendered,
+            "error[E0200]: variable no declarada
+ --> f:2:9
+  |
+2 |         z := 1;
+  |         ^"
+        );
+    }
+
+    #[test]
+    fn renders_a_redeclaration_with_both_declarations() {
+        let source =
+            "begin\n\n\n\n\n\n\n\n    entero total;\n    real\tx, total;\n    total := 1;\nend\n";
+        let errors = crate::semantic::SemanticAnalyzer::new()
+            .parse_all(source)
+            .expect_err("the program should not compile");
+        let diagnostic = Diagnostic::from_error(&errors[0], Locale::En);
+        let rendered = Renderer::new(source, "prog", false, 4, Locale::En).render(&diagnostic);
+        assert_eq!(
+            rendered,
+            "error[E0204]: The variable 'total' at line 10 column 16 was already declared at line 9 column 12
+  --> prog:10:16
+   |
+ 9 |     entero total;
+   |            ----- declared here
+10 |     real    x, total;
+   |                ^~~~~"
+        );
+    }
+
+    #[test]
+    fn colors_only_the_marks_and_the_header() {
+        let rendered =
+            Renderer::new("begin\nz\nend", "f", true, 4, Locale::Es).render(&error_at(2, 1, 2));
+        assert!(rendered
+            .starts_with("\x1b[1;31merror[E0200]:\x1b[0m\x1b[1m variable no declarada\x1b[0m"));
+        assert!(rendered.ends_with("\x1b[1;31m^\x1b[0m"));
+    }
+
+    #[test]
+    fn a_diagnostic_without_span_is_only_its_header() {
+        let diagnostic = Diagnostic::new(Severity::Error, "E0303", "división entre cero", None);
+        assert_eq!(
+            Renderer::new("", "f", false, 4, Locale::Es).render(&diagnostic),
+            "error[E0303]: división entre cero"
+        );
+    }
+
+    #[test]
+    fn serializes_a_lexical_and_a_runtime_error() {
+        let mut lexic = crate::lexic::LexicAnalyzer::new("a := 1;\n  @");
+        lexic.by_ref().for_each(drop);
+        let diagnostics = vec![
+            Diagnostic::from_lexic(&lexic.errors[0], Locale::En),
+            Diagnostic::from_runtime(&InterpreterError::from_division(2), Locale::En),
+        ];
+        assert_eq!(
+            to_json(&diagnostics, "dir\\my \"prog\"\n.txt"),
+            concat!(
+                "[{\"severity\":\"error\",\"code\":\"E0002\",",
+                "\"message\":\"Invalid character '@' at line 2 column 3\",",
+                "\"file\":\"dir\\\\my \\\"prog\\\"\\n.txt\",",
+                "\"start_line\":2,\"start_column\":3,\"end_line\":2,\"end_column\":4,",
+                "\"labels\":[]},",
+                "{\"severity\":\"error\",\"code\":\"E0303\",",
+                "\"message\":\"Integer division by zero at instruction 3\",",
+                "\"file\":\"dir\\\\my \\\"prog\\\"\\n.txt\",",
+                "\"start_line\":null,\"start_column\":null,\"end_line\":null,\"end_column\":null,",
+                "\"labels\":[]}]"
+            )
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_backslashes_and_control_characters() {
+        assert_eq!(
+            escape("say \"hi\"\\\n\t\r\u{1}"),
+            "say \\\"hi\\\"\\\\\\n\\t\\r\\u0001"
+        );
+    }
+}
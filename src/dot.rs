@@ -0,0 +1,13 @@
+/// Escapes a label so it can be written inside a quoted Graphviz string
+pub fn escape(label: &str) -> String {
+    let mut escaped = String::new();
+    for character in label.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,82 @@
+use std::{error, fmt};
+
+use crate::{
+    messages::{format, Locale},
+    semantic::ir::{Label, Operand},
+};
+
+#[derive(Debug, Clone)]
+pub enum InterpreterErrorType {
+    UndefinedLabel(Label),
+    Uninitialized(Operand),
+    InvalidConstant(String),
+    DivisionByZero(usize),
+    StepLimit(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct InterpreterError {
+    pub error_type: InterpreterErrorType,
+}
+
+impl InterpreterError {
+    pub fn from_label(label: Label) -> Self {
+        InterpreterError {
+            error_type: InterpreterErrorType::UndefinedLabel(label),
+        }
+    }
+
+    pub fn from_uninitialized(operand: Operand) -> Self {
+        InterpreterError {
+            error_type: InterpreterErrorType::Uninitialized(operand),
+        }
+    }
+
+    pub fn from_constant(lexeme: &str) -> Self {
+        InterpreterError {
+            error_type: InterpreterErrorType::InvalidConstant(lexeme.to_string()),
+        }
+    }
+
+    pub fn from_division(instruction: usize) -> Self {
+        InterpreterError {
+            error_type: InterpreterErrorType::DivisionByZero(instruction),
+        }
+    }
+
+    pub fn from_step_limit(steps: usize) -> Self {
+        InterpreterError {
+            error_type: InterpreterErrorType::StepLimit(steps),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.error_type {
+            InterpreterErrorType::UndefinedLabel(_) => "E0300",
+            InterpreterErrorType::Uninitialized(_) => "E0301",
+            InterpreterErrorType::InvalidConstant(_) => "E0302",
+            InterpreterErrorType::DivisionByZero(_) => "E0303",
+            InterpreterErrorType::StepLimit(_) => "E0304",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        let code = self.code();
+        match &self.error_type {
+            InterpreterErrorType::UndefinedLabel(label) => format(code, locale, &[label]),
+            InterpreterErrorType::Uninitialized(operand) => format(code, locale, &[operand]),
+            InterpreterErrorType::InvalidConstant(lexeme) => format(code, locale, &[lexeme]),
+            InterpreterErrorType::DivisionByZero(instruction) => {
+                format(code, locale, &[&(instruction + 1)])
+            }
+            InterpreterErrorType::StepLimit(steps) => format(code, locale, &[steps]),
+        }
+    }
+}
+
+impl error::Error for InterpreterError {}
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Locale::default()))
+    }
+}
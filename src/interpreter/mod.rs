@@ -0,0 +1,336 @@
+pub mod error;
+
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{
+    semantic::ir::{BinaryOperator, Code, Instruction, Label, Operand, RelationalOperator},
+    symbols::{SymbolsTable, VariableType},
+};
+
+use self::error::InterpreterError;
+
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Entero(i64),
+    Real(f64),
+}
+
+impl Value {
+    pub fn zero(variable_type: &VariableType) -> Self {
+        match variable_type {
+            VariableType::Entero => Value::Entero(0),
+            VariableType::Real => Value::Real(0.0),
+        }
+    }
+
+    pub fn parse(lexeme: &str) -> Result<Self, InterpreterError> {
+        if lexeme.contains('.') {
+            return lexeme
+                .parse()
+                .map(Value::Real)
+                .map_err(|_| InterpreterError::from_constant(lexeme));
+        }
+        lexeme
+            .parse()
+            .map(Value::Entero)
+            .map_err(|_| InterpreterError::from_constant(lexeme))
+    }
+
+    pub fn as_real(&self) -> f64 {
+        match self {
+            Value::Entero(value) => *value as f64,
+            Value::Real(value) => *value,
+        }
+    }
+
+    /// Converts the value to the declared type of the variable it is stored in
+    pub fn cast(&self, variable_type: &VariableType) -> Self {
+        match (variable_type, self) {
+            (VariableType::Entero, Value::Real(value)) => Value::Entero(*value as i64),
+            (VariableType::Real, Value::Entero(value)) => Value::Real(*value as f64),
+            _ => *self,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Entero(value) => write!(f, "{}", value),
+            Value::Real(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    pub variables: Vec<(String, Value)>,
+}
+
+impl State {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.variables
+            .iter()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, value)| value)
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.variables {
+            writeln!(f, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+pub type InterpreterResult = Result<State, InterpreterError>;
+
+pub struct Interpreter<'a> {
+    table: &'a SymbolsTable,
+    variables: HashMap<String, (VariableType, Value)>,
+    temporaries: HashMap<u32, Value>,
+    pub step_limit: usize,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(table: &'a SymbolsTable) -> Self {
+        Interpreter {
+            table,
+            variables: HashMap::new(),
+            temporaries: HashMap::new(),
+            step_limit: DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.temporaries.clear();
+        self.variables = self
+            .table
+            .variables
+            .iter()
+            .map(|variable| {
+                let value = Value::zero(&variable.variable_type);
+                (
+                    variable.token.lexeme.clone(),
+                    (variable.variable_type.clone(), value),
+                )
+            })
+            .collect();
+    }
+
+    fn labels(code: &Code) -> HashMap<Label, usize> {
+        code.instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, instruction)| match instruction {
+                Instruction::Label(label) => Some((*label, index)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn read(&self, operand: &Operand) -> Result<Value, InterpreterError> {
+        let value = match operand {
+            Operand::Constant(lexeme) => return Value::parse(lexeme),
+            Operand::Variable(name) => self.variables.get(name).map(|(_, value)| *value),
+            Operand::Temporary(index) => self.temporaries.get(index).copied(),
+        };
+        value.ok_or_else(|| InterpreterError::from_uninitialized(operand.clone()))
+    }
+
+    fn write(&mut self, name: &str, value: Value) -> Result<(), InterpreterError> {
+        match self.variables.get_mut(name) {
+            Some((variable_type, current)) => {
+                *current = value.cast(variable_type);
+                Ok(())
+            }
+            None => Err(InterpreterError::from_uninitialized(Operand::Variable(
+                name.to_string(),
+            ))),
+        }
+    }
+
+    fn binary(
+        left: Value,
+        op: &BinaryOperator,
+        right: Value,
+        position: usize,
+    ) -> Result<Value, InterpreterError> {
+        if let (Value::Entero(left), Value::Entero(right)) = (left, right) {
+            return match op {
+                BinaryOperator::Add => Ok(Value::Entero(left.wrapping_add(right))),
+                BinaryOperator::Sub => Ok(Value::Entero(left.wrapping_sub(right))),
+                BinaryOperator::Mul => Ok(Value::Entero(left.wrapping_mul(right))),
+                BinaryOperator::Div => left
+                    .checked_div(right)
+                    .map(Value::Entero)
+                    .ok_or_else(|| InterpreterError::from_division(position)),
+            };
+        }
+        let (left, right) = (left.as_real(), right.as_real());
+        Ok(Value::Real(match op {
+            BinaryOperator::Add => left + right,
+            BinaryOperator::Sub => left - right,
+            BinaryOperator::Mul => left * right,
+            BinaryOperator::Div => left / right,
+        }))
+    }
+
+    fn compare(left: Value, op: &RelationalOperator, right: Value) -> bool {
+        let ordering = match (left, right) {
+            (Value::Entero(left), Value::Entero(right)) => Some(left.cmp(&right)),
+            _ => left.as_real().partial_cmp(&right.as_real()),
+        };
+        let ordering = match ordering {
+            Some(ordering) => ordering,
+            None => return *op == RelationalOperator::NotEqual,
+        };
+        match op {
+            RelationalOperator::Equal => ordering.is_eq(),
+            RelationalOperator::NotEqual => ordering.is_ne(),
+            RelationalOperator::Less => ordering.is_lt(),
+            RelationalOperator::LessEqual => ordering.is_le(),
+            RelationalOperator::Greater => ordering.is_gt(),
+            RelationalOperator::GreaterEqual => ordering.is_ge(),
+        }
+    }
+
+    fn jump(labels: &HashMap<Label, usize>, label: &Label) -> Result<usize, InterpreterError> {
+        labels
+            .get(label)
+            .copied()
+            .ok_or_else(|| InterpreterError::from_label(*label))
+    }
+
+    pub fn run(&mut self, code: &Code) -> InterpreterResult {
+        self.reset();
+        let labels = Interpreter::labels(code);
+        let mut position = 0;
+        let mut steps = 0;
+        while let Some(instruction) = code.instructions.get(position) {
+            steps += 1;
+            if steps > self.step_limit {
+                return Err(InterpreterError::from_step_limit(self.step_limit));
+            }
+            position = match instruction {
+                Instruction::Assign { variable, value } => {
+                    let value = self.read(value)?;
+                    self.write(variable, value)?;
+                    position + 1
+                }
+                Instruction::BinOp {
+                    temp,
+                    left,
+                    op,
+                    right,
+                } => {
+                    let value =
+                        Interpreter::binary(self.read(left)?, op, self.read(right)?, position)?;
+                    self.temporaries.insert(*temp, value);
+                    position + 1
+                }
+                Instruction::Label(_) => position + 1,
+                Instruction::Jump(label) => Interpreter::jump(&labels, label)?,
+                Instruction::CondJumpFalse {
+                    left,
+                    op,
+                    right,
+                    target,
+                } => match Interpreter::compare(self.read(left)?, op, self.read(right)?) {
+                    true => position + 1,
+                    false => Interpreter::jump(&labels, target)?,
+                },
+            };
+        }
+        Ok(self.state())
+    }
+
+    pub fn state(&self) -> State {
+        let variables = self
+            .table
+            .variables
+            .iter()
+            .filter_map(|variable| {
+                let name = &variable.token.lexeme;
+                self.variables
+                    .get(name)
+                    .map(|(_, value)| (name.clone(), *value))
+            })
+            .collect();
+        State { variables }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic::SemanticAnalyzer;
+
+    use super::*;
+
+    fn run(source: &str) -> State {
+        let mut semantic = SemanticAnalyzer::new();
+        let code = semantic.parse(source).expect("the program should compile");
+        Interpreter::new(&semantic.table)
+            .run(&code)
+            .expect("the program should run")
+    }
+
+    #[test]
+    fn runs_the_bundled_sample() {
+        let state = run(include_str!("../../test"));
+        assert_eq!(state.get("hola"), Some(&Value::Entero(10)));
+        assert_eq!(state.get("jefe"), Some(&Value::Entero(32)));
+        assert_eq!(state.get("adios"), Some(&Value::Real(1.0)));
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        let state = run("begin entero a, b; a := 2 * 3 + 4; b := 2 + 3 * 4; end");
+        assert_eq!(state.get("a"), Some(&Value::Entero(10)));
+        assert_eq!(state.get("b"), Some(&Value::Entero(14)));
+    }
+
+    #[test]
+    fn operators_group_left_to_right() {
+        let state = run(
+            "begin entero a, b; real c; a := 10 - 4 - 3; b := 24 / 4 / 2; c := 1.0 - 0.5 - 0.25; end",
+        );
+        assert_eq!(state.get("a"), Some(&Value::Entero(3)));
+        assert_eq!(state.get("b"), Some(&Value::Entero(3)));
+        assert_eq!(state.get("c"), Some(&Value::Real(0.25)));
+    }
+
+    #[test]
+    fn conditions_combine_and_or_not_over_arithmetic() {
+        let state = run("begin entero i, j, n, k;
+            i := 0; n := 0; k := 0;
+            while (i < 4)
+                j := 0;
+                while (j < 4)
+                    if ((i + j > 3 or i * 2 = j) and not (j - i < 0 or i = 3)) n := n + 1; end;
+                    if (not (i < 2) and not (j >= 2) or i + 1 = j) k := k + 1; end;
+                    j := j + 1;
+                endwhile;
+                i := i + 1;
+            endwhile;
+        end");
+        assert_eq!(state.get("n"), Some(&Value::Entero(5)));
+        assert_eq!(state.get("k"), Some(&Value::Entero(7)));
+    }
+
+    #[test]
+    fn loops_stop_when_their_condition_short_circuits() {
+        let state = run("begin entero i, m;
+            i := 0; m := 0;
+            while (i < 10 and i * i < 20) i := i + 1; endwhile;
+            while (not (m >= 3) or m = 3) m := m + 1; endwhile;
+        end");
+        assert_eq!(state.get("i"), Some(&Value::Entero(5)));
+        assert_eq!(state.get("m"), Some(&Value::Entero(4)));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{error, fmt};
+
+use crate::{
+    messages::{format, Locale},
+    token::Token,
+};
+
+pub const MAX_IDENTIFIER_LENGTH: usize = 32;
+
+#[derive(Debug, Clone)]
+pub enum LexicErrorType {
+    LoneColon,
+    InvalidCharacter,
+    MalformedNumber,
+    IdentifierTooLong,
+    UnterminatedComment,
+}
+
+/// Error found while reading a token, `token` holds the offending text and where it starts
+#[derive(Debug, Clone)]
+pub struct LexicError {
+    pub error_type: LexicErrorType,
+    pub token: Token,
+}
+
+impl LexicError {
+    pub fn new(error_type: LexicErrorType, token: &Token) -> Self {
+        LexicError {
+            error_type,
+            token: token.clone(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.error_type {
+            LexicErrorType::LoneColon => "E0001",
+            LexicErrorType::InvalidCharacter => "E0002",
+            LexicErrorType::MalformedNumber => "E0003",
+            LexicErrorType::IdentifierTooLong => "E0004",
+            LexicErrorType::UnterminatedComment => "E0005",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        let token = &self.token;
+        match self.error_type {
+            LexicErrorType::LoneColon | LexicErrorType::UnterminatedComment => {
+                format(self.code(), locale, &[&token.line, &token.col])
+            }
+            // Control characters such as a NUL byte are shown escaped, not written raw
+            LexicErrorType::InvalidCharacter => {
+                let shown: String = token
+                    .lexeme
+                    .chars()
+                    .map(|character| match character.is_control() {
+                        true => character.escape_debug().to_string(),
+                        false => character.to_string(),
+                    })
+                    .collect();
+                format(self.code(), locale, &[&shown, &token.line, &token.col])
+            }
+            LexicErrorType::MalformedNumber => format(
+                self.code(),
+                locale,
+                &[&token.lexeme, &token.line, &token.col],
+            ),
+            LexicErrorType::IdentifierTooLong => format(
+                self.code(),
+                locale,
+                &[
+                    &token.lexeme,
+                    &token.line,
+                    &token.col,
+                    &MAX_IDENTIFIER_LENGTH,
+                ],
+            ),
+        }
+    }
+
+    /// Column right after the last character of the offending text
+    pub fn end_col(&self) -> u32 {
+        self.token.col + self.token.lexeme.chars().count() as u32
+    }
+}
+
+impl error::Error for LexicError {}
+impl fmt::Display for LexicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Locale::default()))
+    }
+}
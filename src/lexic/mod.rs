@@ -0,0 +1,593 @@
+pub mod error;
+
+use std::str::Chars;
+
+use crate::token::{Comment, Token, TokenType};
+
+use self::error::{LexicError, LexicErrorType, MAX_IDENTIFIER_LENGTH};
+
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+#[derive(Clone)]
+pub struct LexicAnalyzer<'a> {
+    pub input: String,
+    pub current: char,
+    pub iter: Chars<'a>,
+    pub current_line: usize,
+    pub current_col: usize,
+    pub current_offset: usize,
+    pub tab_width: usize,
+    pub errors: Vec<LexicError>,
+    /// Comments found so far, in the order of the source
+    pub comments: Vec<Comment>,
+}
+
+impl<'a> LexicAnalyzer<'a> {
+    pub fn new(input: &'a str) -> LexicAnalyzer<'a> {
+        LexicAnalyzer::with_tab_width(input, DEFAULT_TAB_WIDTH)
+    }
+
+    /// Lexer whose columns treat a tab as advancing to the next multiple of `tab_width`
+    pub fn with_tab_width(input: &'a str, tab_width: usize) -> LexicAnalyzer<'a> {
+        let mut iter = input.chars();
+        let current = iter.next().unwrap_or('\0');
+        LexicAnalyzer {
+            iter,
+            input: input.to_string(),
+            current,
+            current_line: 1,
+            current_col: 1,
+            current_offset: 0,
+            tab_width: tab_width.max(1),
+            errors: Vec::new(),
+            comments: Vec::new(),
+        }
+    }
+
+    /// Moves past the current character. Lines and columns start at 1 and always describe
+    /// the position of `current`, the offset is in bytes from the start of the input.
+    pub fn next_char(&mut self) -> char {
+        if self.at_end() {
+            return self.current;
+        }
+        match self.current {
+            '\n' => {
+                self.current_line += 1;
+                self.current_col = 1;
+            }
+            '\t' => self.current_col += self.tab_width - (self.current_col - 1) % self.tab_width,
+            _ => self.current_col += 1,
+        }
+        self.current_offset += self.current.len_utf8();
+        self.current = self.iter.next().unwrap_or('\0');
+        self.current
+    }
+
+    /// Whether the whole input was read. `current` is then `'\0'`, but a NUL byte inside the
+    /// input is an invalid character, not the end.
+    pub fn at_end(&self) -> bool {
+        self.current_offset >= self.input.len()
+    }
+
+    /// Character after the current one, without advancing
+    pub fn peek_char(&self) -> char {
+        self.iter.clone().next().unwrap_or('\0')
+    }
+
+    pub fn report(&mut self, error_type: LexicErrorType, token: &Token) {
+        self.errors.push(LexicError::new(error_type, token));
+    }
+
+    /// Skips whitespace, `// ...` line comments and `{ ... }` block comments
+    pub fn skip_empty(&mut self) {
+        loop {
+            match self.current {
+                '\n' | '\t' | '\r' | ' ' => {
+                    self.next_char();
+                }
+                '/' if self.peek_char() == '/' => {
+                    let (line, col) = (self.current_line as u32, self.current_col as u32);
+                    let start = self.current_offset;
+                    while self.current != '\n' && !self.at_end() {
+                        self.next_char();
+                    }
+                    let text = self.input[start..self.current_offset].trim_end();
+                    self.comment(text.to_string(), line, col, start);
+                }
+                '{' => self.block_comment(),
+                _ => break,
+            }
+        }
+    }
+
+    pub fn block_comment(&mut self) {
+        let start = Token {
+            token_type: TokenType::Unknown,
+            lexeme: self.current.to_string(),
+            line: self.current_line as u32,
+            col: self.current_col as u32,
+            offset: self.current_offset,
+        };
+        self.next_char();
+        while self.current != '}' && !self.at_end() {
+            self.next_char();
+        }
+        match self.current {
+            '}' => {
+                self.next_char();
+                let text = self.input[start.offset..self.current_offset].to_string();
+                self.comment(text, start.line, start.col, start.offset);
+            }
+            _ => self.report(LexicErrorType::UnterminatedComment, &start),
+        }
+    }
+
+    fn comment(&mut self, text: String, line: u32, col: u32, offset: usize) {
+        self.comments.push(Comment {
+            text,
+            line,
+            col,
+            offset,
+            following: TokenType::Unknown,
+        });
+    }
+
+    pub fn single_character_token(&mut self) -> Option<Token> {
+        let token_type = match self.current {
+            ',' => Some(TokenType::Coma),
+            ';' => Some(TokenType::Semicolon),
+            '(' => Some(TokenType::ParentesisAbierto),
+            ')' => Some(TokenType::ParentesisCerrado),
+            '.' => Some(TokenType::Punto),
+            _ => None,
+        };
+        if let Some(token_type) = token_type {
+            let token = Token {
+                token_type,
+                lexeme: self.current.to_string(),
+                line: self.current_line as u32,
+                col: self.current_col as u32,
+                offset: self.current_offset,
+            };
+            self.next_char();
+            return Some(token);
+        }
+        None
+    }
+
+    pub fn digits(&mut self, lexeme: &mut String) {
+        while let '0'..='9' = self.current {
+            lexeme.push(self.current);
+            self.next_char();
+        }
+    }
+
+    /// Reads the fractional part of a number whose integer part is in `number`, the current
+    /// character is the `.`. Both `3.` and `1.2.3` are reported as malformed.
+    pub fn real_number(&mut self, number: Token) -> Token {
+        let mut token = Token {
+            token_type: TokenType::Real,
+            ..number
+        };
+        token.lexeme.push(self.current);
+        self.next_char();
+        if !self.current.is_ascii_digit() {
+            self.report(LexicErrorType::MalformedNumber, &token);
+            return token;
+        }
+        self.digits(&mut token.lexeme);
+        if self.current == '.' && self.peek_char().is_ascii_digit() {
+            while self.current == '.' && self.peek_char().is_ascii_digit() {
+                token.lexeme.push(self.current);
+                self.next_char();
+                self.digits(&mut token.lexeme);
+            }
+            self.report(LexicErrorType::MalformedNumber, &token);
+        }
+        token
+    }
+
+    pub fn number(&mut self) -> Token {
+        let mut token = Token {
+            lexeme: String::new(),
+            col: self.current_col as u32,
+            offset: self.current_offset,
+            line: self.current_line as u32,
+            token_type: TokenType::Entero,
+        };
+        self.digits(&mut token.lexeme);
+        if self.current == '.' {
+            return self.real_number(token);
+        }
+        token
+    }
+
+    pub fn operator(&mut self) -> Token {
+        let token = Token {
+            token_type: match self.current {
+                '*' | '/' => TokenType::OperadorAritB,
+                _ => TokenType::OperadorAritA,
+            },
+            lexeme: self.current.to_string(),
+            line: self.current_line as u32,
+            col: self.current_col as u32,
+            offset: self.current_offset,
+        };
+        self.next_char();
+        token
+    }
+
+    pub fn logic_operator(&mut self) -> Token {
+        let mut token = Token {
+            token_type: TokenType::OperadorCondicion,
+            lexeme: self.current.to_string(),
+            line: self.current_line as u32,
+            col: self.current_col as u32,
+            offset: self.current_offset,
+        };
+        let current = self.current;
+        self.next_char();
+        if let '>' | '<' = current {
+            if self.current == '=' {
+                token.lexeme.push(self.current);
+                self.next_char();
+            }
+        }
+        if current == '<' && self.current == '>' {
+            token.lexeme.push(self.current);
+            self.next_char();
+        }
+        token
+    }
+
+    pub fn asign_operator(&mut self) -> Token {
+        let mut token = Token {
+            token_type: TokenType::OperadorAsig,
+            lexeme: self.current.to_string(),
+            line: self.current_line as u32,
+            col: self.current_col as u32,
+            offset: self.current_offset,
+        };
+        if self.next_char() == '=' {
+            token.lexeme.push(self.current);
+            self.next_char();
+            return token;
+        }
+        // Most likely a mistyped `:=`, keep going as if it were one
+        self.report(LexicErrorType::LoneColon, &token);
+        token
+    }
+
+    pub fn identifier(&mut self) -> Token {
+        let mut token = Token {
+            token_type: TokenType::Id,
+            lexeme: String::new(),
+            line: self.current_line as u32,
+            col: self.current_col as u32,
+            offset: self.current_offset,
+        };
+        while let '0'..='9' | 'a'..='z' | 'A'..='Z' = self.current {
+            token.lexeme.push(self.current);
+            self.next_char();
+        }
+        if token.lexeme.chars().count() > MAX_IDENTIFIER_LENGTH {
+            self.report(LexicErrorType::IdentifierTooLong, &token);
+        }
+        token
+    }
+
+    pub fn reserved_word(id_token: &Token) -> Option<Token> {
+        let token_type = match id_token.lexeme.as_str() {
+            "real" | "entero" => Some(TokenType::Tipo),
+            "begin" => Some(TokenType::Begin),
+            "end" => Some(TokenType::End),
+            "if" => Some(TokenType::If),
+            "else" => Some(TokenType::Else),
+            "while" => Some(TokenType::While),
+            "endwhile" => Some(TokenType::Endwhile),
+            "and" => Some(TokenType::And),
+            "or" => Some(TokenType::Or),
+            "not" => Some(TokenType::Not),
+            _ => None,
+        };
+        if let Some(token_type) = token_type {
+            let mut token = id_token.clone();
+            token.token_type = token_type;
+            return Some(token);
+        }
+        None
+    }
+
+    /// Next token of the input, the comments skipped before it learn which token follows them
+    pub fn next_token(&mut self) -> Token {
+        let pending = self.comments.len();
+        let token = self.read_token();
+        for comment in &mut self.comments[pending..] {
+            comment.following = token.token_type.clone();
+        }
+        token
+    }
+
+    /// Reads the next token. Invalid characters are reported and skipped in a loop, so a long
+    /// run of them, such as a binary file, does not grow the stack.
+    fn read_token(&mut self) -> Token {
+        loop {
+            self.skip_empty();
+            if let Some(token) = self.single_character_token() {
+                return token;
+            }
+
+            let token = match self.current {
+                '0'..='9' => self.number(),
+                ':' => self.asign_operator(),
+                '+' | '-' | '*' | '/' => self.operator(),
+                '=' | '<' | '>' => self.logic_operator(),
+                'a'..='z' | 'A'..='Z' => {
+                    let token = self.identifier();
+                    LexicAnalyzer::reserved_word(&token).unwrap_or(token)
+                }
+                '\0' if self.at_end() => Token {
+                    token_type: TokenType::EOF,
+                    lexeme: self.current.to_string(),
+                    line: self.current_line as u32,
+                    col: self.current_col as u32,
+                    offset: self.current_offset,
+                },
+
+                _ => {
+                    let token = Token {
+                        token_type: TokenType::Unknown,
+                        lexeme: self.current.to_string(),
+                        line: self.current_line as u32,
+                        col: self.current_col as u32,
+                        offset: self.current_offset,
+                    };
+                    self.report(LexicErrorType::InvalidCharacter, &token);
+                    self.next_char();
+                    continue;
+                }
+            };
+            return token;
+        }
+    }
+}
+
+impl<'a> Iterator for LexicAnalyzer<'a> {
+    type Item = Token;
+
+    /// Yields tokens until the end of the input, the `EOF` token itself is not returned
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.next_token();
+        match token.token_type {
+            TokenType::EOF => None,
+            _ => Some(token),
+        }
+    }
+}
+
+pub fn tokenize(input: &str) -> Vec<Token> {
+    LexicAnalyzer::new(input).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types(input: &str) -> Vec<TokenType> {
+        tokenize(input)
+            .into_iter()
+            .map(|token| token.token_type)
+            .collect()
+    }
+
+    fn lexemes(input: &str) -> Vec<String> {
+        tokenize(input)
+            .into_iter()
+            .map(|token| token.lexeme)
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_a_program() {
+        let input = "begin entero a; a := 2 * (a + 1.5); end";
+        assert_eq!(
+            types(input),
+            vec![
+                TokenType::Begin,
+                TokenType::Tipo,
+                TokenType::Id,
+                TokenType::Semicolon,
+                TokenType::Id,
+                TokenType::OperadorAsig,
+                TokenType::Entero,
+                TokenType::OperadorAritB,
+                TokenType::ParentesisAbierto,
+                TokenType::Id,
+                TokenType::OperadorAritA,
+                TokenType::Real,
+                TokenType::ParentesisCerrado,
+                TokenType::Semicolon,
+                TokenType::End,
+            ]
+        );
+        assert_eq!(
+            lexemes(input),
+            vec![
+                "begin", "entero", "a", ";", "a", ":=", "2", "*", "(", "a", "+", "1.5", ")", ";",
+                "end"
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_keywords_and_operators() {
+        assert_eq!(
+            types("if else while endwhile and or not real"),
+            vec![
+                TokenType::If,
+                TokenType::Else,
+                TokenType::While,
+                TokenType::Endwhile,
+                TokenType::And,
+                TokenType::Or,
+                TokenType::Not,
+                TokenType::Tipo,
+            ]
+        );
+        assert_eq!(
+            lexemes("<= >= <> < > = - /"),
+            vec!["<=", ">=", "<>", "<", ">", "=", "-", "/"]
+        );
+        assert!(types("<= >= <> < > =")
+            .iter()
+            .all(|token_type| *token_type == TokenType::OperadorCondicion));
+    }
+
+    #[test]
+    fn skips_long_runs_of_invalid_characters() {
+        let input = format!("{}a", "@".repeat(100_000));
+        let mut lexic = LexicAnalyzer::new(&input);
+        let tokens: Vec<Token> = lexic.by_ref().collect();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].lexeme, "a");
+        assert_eq!(lexic.errors.len(), 100_000);
+    }
+
+    #[test]
+    fn a_nul_byte_inside_the_input_is_an_invalid_character() {
+        let mut lexic = LexicAnalyzer::new("end\0 garbage ;");
+        assert_eq!(lexic.by_ref().count(), 3);
+        let errors: Vec<(&str, u32, u32)> = lexic
+            .errors
+            .iter()
+            .map(|error| (error.code(), error.token.line, error.token.col))
+            .collect();
+        assert_eq!(errors, vec![("E0002", 1, 4)]);
+        assert_eq!(
+            lexic.errors[0].message(crate::messages::Locale::En),
+            "Invalid character '\\0' at line 1 column 4"
+        );
+        // Comments do not end at a NUL byte either
+        assert_eq!(lexemes("// a\0b\nc { d\0e } f"), vec!["c", "f"]);
+    }
+
+    #[test]
+    fn stops_at_the_end_of_the_input() {
+        assert!(tokenize("").is_empty());
+        assert!(tokenize(" \n\t ").is_empty());
+        assert_eq!(lexemes("a\n"), vec!["a"]);
+    }
+
+    /// Line, column and byte offset of every token
+    fn positions(tokens: &[Token]) -> Vec<(u32, u32, usize)> {
+        tokens
+            .iter()
+            .map(|token| (token.line, token.col, token.offset))
+            .collect()
+    }
+
+    /// Lexeme and span of every error, the span goes from its column up to `end_col`
+    fn error_spans(input: &str) -> Vec<(String, u32, u32, u32)> {
+        let mut lexic = LexicAnalyzer::new(input);
+        lexic.by_ref().for_each(drop);
+        lexic
+            .errors
+            .iter()
+            .map(|error| {
+                let token = &error.token;
+                (token.lexeme.clone(), token.line, token.col, error.end_col())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn positions_start_at_one_on_every_line() {
+        assert_eq!(
+            positions(&tokenize(
+                "ab cd
+  ef
+gh"
+            )),
+            vec![(1, 1, 0), (1, 4, 3), (2, 3, 8), (3, 1, 11)]
+        );
+    }
+
+    #[test]
+    fn tabs_advance_to_the_next_tab_stop() {
+        assert_eq!(
+            positions(&tokenize("\ta b\tc")),
+            vec![(1, 5, 1), (1, 7, 3), (1, 9, 5)]
+        );
+        let tokens: Vec<Token> = LexicAnalyzer::with_tab_width("a\tb\n\t\tc", 8).collect();
+        assert_eq!(positions(&tokens), vec![(1, 1, 0), (1, 9, 2), (2, 17, 6)]);
+    }
+
+    #[test]
+    fn crlf_line_endings_count_as_one_line() {
+        assert_eq!(
+            positions(&tokenize("a\r\nb\r\n\r\n c")),
+            vec![(1, 1, 0), (2, 1, 3), (4, 2, 9)]
+        );
+    }
+
+    #[test]
+    fn comments_keep_the_following_positions() {
+        assert_eq!(
+            positions(&tokenize("a { one\ntwo\n } b // three\n c")),
+            vec![(1, 1, 0), (3, 4, 15), (4, 2, 27)]
+        );
+    }
+
+    #[test]
+    fn offsets_count_bytes_and_columns_count_characters() {
+        assert_eq!(positions(&tokenize("ñ a")), vec![(1, 3, 3)]);
+    }
+
+    #[test]
+    fn errors_span_the_offending_text() {
+        assert_eq!(error_spans("a : b"), vec![(String::from(":"), 1, 3, 4)]);
+        assert_eq!(error_spans("a\n  @"), vec![(String::from("@"), 2, 3, 4)]);
+        assert_eq!(
+            error_spans("a := 3. ;\nb := 1.2.3;"),
+            vec![
+                (String::from("3."), 1, 6, 8),
+                (String::from("1.2.3"), 2, 6, 11)
+            ]
+        );
+        let long = "x".repeat(MAX_IDENTIFIER_LENGTH + 1);
+        assert_eq!(
+            error_spans(&format!(" {}", long)),
+            vec![(long, 1, 2, MAX_IDENTIFIER_LENGTH as u32 + 3)]
+        );
+        assert_eq!(
+            error_spans("a\n  { b\n c"),
+            vec![(String::from("{"), 2, 3, 4)]
+        );
+    }
+
+    #[test]
+    fn keeps_comments_with_the_token_that_follows_them() {
+        let mut lexic = LexicAnalyzer::new("a // one\r\n{ two\n} b");
+        assert_eq!(lexic.by_ref().count(), 2);
+        let comments: Vec<(&str, u32, u32, usize, TokenType)> = lexic
+            .comments
+            .iter()
+            .map(|comment| {
+                (
+                    comment.text.as_str(),
+                    comment.line,
+                    comment.col,
+                    comment.offset,
+                    comment.following.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                ("// one", 1, 3, 2, TokenType::Id),
+                ("{ two\n}", 2, 1, 10, TokenType::Id)
+            ]
+        );
+    }
+}
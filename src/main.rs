@@ -1,19 +1,213 @@
-use std::{env::args, fs};
+use std::{env::args, process::ExitCode};
 
-use semantic::SemanticAnalyzer;
+use ast::{format::Formatter, lower::lower};
+use cli::{Command, Options, EXIT_DIAGNOSTICS, EXIT_OK, EXIT_RUNTIME, EXIT_USAGE};
+use diagnostics::{to_json, Diagnostic, Renderer};
+use interpreter::Interpreter;
+use lexic::LexicAnalyzer;
+use semantic::{cfg::ControlFlowGraph, graph::graphs_to_dot, SemanticAnalyzer};
+use sintactic::SintacticAnalyzer;
+use token::{tokens_table, Token};
 
+pub mod ast;
+pub mod cli;
+pub mod diagnostics;
+pub mod dot;
+pub mod interpreter;
 pub mod lexic;
+pub mod messages;
 pub mod production;
 pub mod semantic;
 pub mod sintactic;
 pub mod symbols;
 pub mod token;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let path = args().nth(1).expect("No file path given");
-    let contets = fs::read_to_string(path)?;
-    let mut semantic = SemanticAnalyzer::new();
-    let res = semantic.parse(&contets)?;
-    println!("{}", res);
-    Ok(())
+/// Writes the diagnostics to the standard error, as a JSON array when `--json` is given
+fn report(source: &str, options: &Options, diagnostics: &[Diagnostic]) {
+    if options.json {
+        eprintln!("{}", to_json(diagnostics, options.source_name()));
+        return;
+    }
+    let renderer = Renderer::new(
+        source,
+        options.source_name(),
+        options.color,
+        options.tab_width,
+        options.locale,
+    );
+    for diagnostic in diagnostics {
+        eprintln!("{}\n", renderer.render(diagnostic));
+    }
+}
+
+fn tokens(source: &str, options: &Options) -> u8 {
+    let mut lexic = LexicAnalyzer::with_tab_width(source, options.tab_width);
+    let tokens: Vec<Token> = lexic.by_ref().collect();
+    println!("{}", tokens_table(&tokens, options.locale));
+    let diagnostics: Vec<Diagnostic> = lexic
+        .errors
+        .iter()
+        .map(|error| Diagnostic::from_lexic(error, options.locale))
+        .collect();
+    report(source, options, &diagnostics);
+    match lexic.errors.is_empty() {
+        true => EXIT_OK,
+        false => EXIT_DIAGNOSTICS,
+    }
+}
+
+fn parse(source: &str, options: &Options) -> u8 {
+    let mut sintactic = SintacticAnalyzer::with_tab_width(source, options.tab_width);
+    let (tree, errors) = sintactic.analize_all();
+    let diagnostics: Vec<Diagnostic> = sintactic
+        .lexic
+        .errors
+        .iter()
+        .map(|error| Diagnostic::from_lexic(error, options.locale))
+        .chain(
+            errors
+                .iter()
+                .map(|error| Diagnostic::from_sintactic(error, options.locale)),
+        )
+        .collect();
+    report(source, options, &diagnostics);
+    if options.dot {
+        println!("{}", tree.to_dot(options.locale));
+    } else {
+        println!("{}", tree);
+    }
+    match diagnostics.is_empty() {
+        true => EXIT_OK,
+        false => EXIT_DIAGNOSTICS,
+    }
+}
+
+fn format(source: &str, options: &Options) -> u8 {
+    let mut sintactic = SintacticAnalyzer::with_tab_width(source, options.tab_width);
+    let (tree, errors) = sintactic.analize_all();
+    let mut diagnostics: Vec<Diagnostic> = sintactic
+        .lexic
+        .errors
+        .iter()
+        .map(|error| Diagnostic::from_lexic(error, options.locale))
+        .chain(
+            errors
+                .iter()
+                .map(|error| Diagnostic::from_sintactic(error, options.locale)),
+        )
+        .collect();
+    let program = match lower(&tree) {
+        Ok(program) if diagnostics.is_empty() => program,
+        Ok(_) => {
+            report(source, options, &diagnostics);
+            return EXIT_DIAGNOSTICS;
+        }
+        Err(error) => {
+            diagnostics.push(Diagnostic::from_error(&error, options.locale));
+            report(source, options, &diagnostics);
+            return EXIT_DIAGNOSTICS;
+        }
+    };
+    let formatted =
+        Formatter::new(options.split_declarations).format(&program, &sintactic.lexic.comments);
+    if !options.check {
+        print!("{}", formatted);
+        return EXIT_OK;
+    }
+    match formatted == source {
+        true => EXIT_OK,
+        false => {
+            eprintln!(
+                "{}",
+                messages::format("format-check", options.locale, &[&options.source_name()])
+            );
+            EXIT_DIAGNOSTICS
+        }
+    }
+}
+
+fn compile(source: &str, options: &Options) -> u8 {
+    let mut semantic = SemanticAnalyzer::with_tab_width(options.tab_width);
+    let code = match semantic.parse_all(source) {
+        Ok(code) => code,
+        Err(errors) => {
+            let diagnostics: Vec<Diagnostic> = errors
+                .iter()
+                .map(|error| Diagnostic::from_error(error, options.locale))
+                .collect();
+            report(source, options, &diagnostics);
+            return EXIT_DIAGNOSTICS;
+        }
+    };
+    let mut diagnostics: Vec<Diagnostic> = semantic
+        .warnings
+        .iter()
+        .map(|warning| Diagnostic::from_warning(warning, options.locale))
+        .collect();
+    let mut status = EXIT_OK;
+    let output = match options.command {
+        Command::Ir if options.dag_dot => format!(
+            "{}\n",
+            graphs_to_dot(&semantic.graphs, &semantic.table, options.locale)
+        ),
+        Command::Ir if options.cfg_dot => {
+            format!(
+                "{}\n",
+                ControlFlowGraph::from_code(&code).to_dot(options.locale)
+            )
+        }
+        Command::Ir => code.to_string(),
+        Command::Run => match Interpreter::new(&semantic.table).run(&code) {
+            Ok(state) => state.to_string(),
+            Err(error) => {
+                diagnostics.push(Diagnostic::from_runtime(&error, options.locale));
+                status = EXIT_RUNTIME;
+                String::new()
+            }
+        },
+        _ => String::new(),
+    };
+    // A runtime error joins the warnings, so --json still writes a single array
+    if options.json || !diagnostics.is_empty() {
+        report(source, options, &diagnostics);
+    }
+    print!("{}", output);
+    status
+}
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = args().skip(1).collect();
+    let options = match Options::parse(&arguments) {
+        Ok(options) => options,
+        Err(message) => {
+            let locale = Options::requested_locale(&arguments);
+            eprintln!("{}\n\n{}", message, messages::text("usage", locale));
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    if options.command == Command::Help {
+        println!("{}", messages::text("usage", options.locale));
+        return ExitCode::from(EXIT_OK);
+    }
+    let source = match options.read_source() {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!(
+                "{}",
+                messages::format(
+                    "read-error",
+                    options.locale,
+                    &[&options.source_name(), &error]
+                )
+            );
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let status = match options.command {
+        Command::Tokens => tokens(&source, &options),
+        Command::Parse => parse(&source, &options),
+        Command::Fmt => format(&source, &options),
+        _ => compile(&source, &options),
+    };
+    ExitCode::from(status)
 }
@@ -0,0 +1,340 @@
+use std::fmt;
+
+/// Language used for the texts shown to the user
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Locale {
+    #[default]
+    Es,
+    En,
+}
+
+impl Locale {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "es" => Some(Locale::Es),
+            "en" => Some(Locale::En),
+            _ => None,
+        }
+    }
+}
+
+// Every diagnostic has a stable code that does not depend on the language of its message, and
+// its text is keyed by that code: E00xx lexical, E01xx syntax, E02xx semantic and E03xx
+// runtime errors, and W00xx warnings. `{}` marks are replaced in order by the arguments of
+// `format`.
+const ES: &[(&str, &str)] = &[
+    (
+        "E0001",
+        "Se encontró ':' sin '=' en la linea {} columna {}, se esperaba: ':='",
+    ),
+    ("E0002", "Caracter no válido '{}' en la linea {} columna {}"),
+    ("E0003", "Número mal formado '{}' en la linea {} columna {}"),
+    (
+        "E0004",
+        "El identificador '{}' en la linea {} columna {} excede el máximo de {} caracteres",
+    ),
+    (
+        "E0005",
+        "Comentario sin cerrar iniciado en la linea {} columna {}, se esperaba: '}'",
+    ),
+    (
+        "E0100",
+        "Caracter inesperado '{}', en la linea {} columna {}, se esperaba: '{}'",
+    ),
+    (
+        "E0101",
+        "Caracter inesperado '{}', en la linea {} columna {}, se esperaba: '{}'",
+    ),
+    (
+        "E0102",
+        "Caracter inesperado '{}', en la linea {} columna {}, se esperaba: '{}'",
+    ),
+    (
+        "E0200",
+        "Utilizacion de una variable no declarada '{}' en la linea {} columna {}",
+    ),
+    ("E0201", "Producción con formato inesperado {}"),
+    ("E0202", "Caracter inesperado '{}' en la linea {} columna {}"),
+    (
+        "E0203",
+        "No se puede asignar un valor {} a la variable {} '{}' en la linea {} columna {}",
+    ),
+    (
+        "E0204",
+        "La variable '{}' en la linea {} columna {} ya fue declarada en la linea {} columna {}",
+    ),
+    ("E0205", "División entre cero en la linea {} columna {}"),
+    ("E0300", "Salto a una etiqueta inexistente '{}'"),
+    ("E0301", "Lectura de '{}' antes de asignarle un valor"),
+    ("E0302", "Constante numérica inválida '{}'"),
+    ("E0303", "División entera entre cero en la instrucción {}"),
+    (
+        "E0304",
+        "Se alcanzó el límite de {} instrucciones ejecutadas, posible ciclo infinito",
+    ),
+    (
+        "W0001",
+        "la variable '{}' declarada en la linea {} columna {} nunca se utiliza",
+    ),
+    (
+        "W0002",
+        "a la variable '{}' declarada en la linea {} columna {} se le asigna un valor que nunca se lee",
+    ),
+    (
+        "W0003",
+        "la variable '{}' declarada en la linea {} columna {} se lee pero nunca se le asigna un valor",
+    ),
+    ("warning-prefix", "Advertencia: {}"),
+    ("format-check", "'{}' no tiene el formato canónico"),
+    ("severity-error", "error"),
+    ("severity-warning", "advertencia"),
+    ("label-declared-here", "declarada aquí"),
+    ("label-expected", "se esperaba '{}'"),
+    ("expected-number", "número entero o real"),
+    ("expected-statement", "if, while o una asignación"),
+    ("variable-type-entero", "entero"),
+    ("variable-type-real", "real"),
+    ("token-tipo", "tipo"),
+    ("token-id", "Id"),
+    ("token-entero", "entero"),
+    ("token-real", "real"),
+    ("token-operador-arit-a", "+ o -"),
+    ("token-operador-arit-b", "/ o *"),
+    ("token-operador-condicion", "operador condicional"),
+    ("token-eof", "fin de archivo"),
+    ("token-unknown", "No reconocido"),
+    ("column-type", "Tipo"),
+    ("column-lexeme", "Lexema"),
+    ("column-line", "Linea"),
+    ("column-column", "Columna"),
+    (
+        "usage",
+        "Uso: translator [comando] [opciones] [archivo]
+
+Comandos:
+    tokens    muestra los tokens producidos por el analizador léxico
+    parse     muestra el árbol sintáctico
+    ir        muestra el código de tres direcciones (por defecto)
+    run       ejecuta el programa y muestra el valor final de las variables
+    check     solo reporta errores y advertencias
+    fmt       imprime el programa con el formato canónico
+
+Opciones:
+    --dot         con parse, imprime el árbol en formato Graphviz
+    --dag-dot     con ir, imprime los DAG de las expresiones en formato Graphviz
+    --cfg-dot     con ir, imprime los bloques básicos y sus saltos en formato Graphviz
+    --color       resalta los errores y advertencias con colores ANSI
+    --check       con fmt, solo verifica que el archivo ya tenga el formato canónico
+    --split-decls con fmt, escribe una variable por declaración
+    --json        imprime los errores y advertencias como un arreglo JSON
+    --lang IDIOMA idioma de los mensajes: es (por defecto) o en
+    --tab-width N columnas que avanza un tabulador en las posiciones (por defecto 4)
+    -h, --help    muestra esta ayuda
+
+El comando puede ir en cualquier posición. Si no se da un archivo, o se da '-', el
+programa se lee de la entrada estándar.",
+    ),
+    ("cli-missing-language", "Falta el idioma después de '--lang'"),
+    ("cli-unknown-language", "Idioma desconocido '{}'"),
+    (
+        "cli-missing-tab-width",
+        "Falta el ancho después de '--tab-width'",
+    ),
+    (
+        "cli-invalid-tab-width",
+        "Ancho de tabulación no válido '{}', debe ser un entero positivo",
+    ),
+    ("cli-unknown-option", "Opción desconocida '{}'"),
+    ("cli-unexpected-argument", "Argumento inesperado '{}'"),
+    (
+        "cli-option-command",
+        "La opción '{}' no se puede usar con el comando '{}'",
+    ),
+    (
+        "cli-conflicting-options",
+        "Las opciones '{}' y '{}' no se pueden usar juntas",
+    ),
+    ("read-error", "No se pudo leer '{}': {}"),
+    ("dot-leaf", "linea {}, columna {}"),
+    ("dot-assignment", "{} := (linea {})"),
+    ("dot-condition", "condición {} (linea {})"),
+    ("dot-left", "izq"),
+    ("dot-right", "der"),
+    ("cfg-entry", "inicio"),
+    ("cfg-exit", "fin"),
+    ("cfg-true", "verdadero"),
+    ("cfg-false", "falso"),
+];
+
+const EN: &[(&str, &str)] = &[
+    (
+        "E0001",
+        "Found ':' without '=' at line {} column {}, expected: ':='",
+    ),
+    ("E0002", "Invalid character '{}' at line {} column {}"),
+    ("E0003", "Malformed number '{}' at line {} column {}"),
+    (
+        "E0004",
+        "The identifier '{}' at line {} column {} exceeds the maximum of {} characters",
+    ),
+    (
+        "E0005",
+        "Unterminated comment started at line {} column {}, expected: '}'",
+    ),
+    (
+        "E0100",
+        "Unexpected token '{}', at line {} column {}, expected: '{}'",
+    ),
+    (
+        "E0101",
+        "Unexpected token '{}', at line {} column {}, expected: '{}'",
+    ),
+    (
+        "E0102",
+        "Unexpected token '{}', at line {} column {}, expected: '{}'",
+    ),
+    (
+        "E0200",
+        "Use of an undeclared variable '{}' at line {} column {}",
+    ),
+    ("E0201", "Production with an unexpected format {}"),
+    ("E0202", "Unexpected token '{}' at line {} column {}"),
+    (
+        "E0203",
+        "Cannot assign a {} value to the {} variable '{}' at line {} column {}",
+    ),
+    (
+        "E0204",
+        "The variable '{}' at line {} column {} was already declared at line {} column {}",
+    ),
+    ("E0205", "Division by zero at line {} column {}"),
+    ("E0300", "Jump to a nonexistent label '{}'"),
+    ("E0301", "Read of '{}' before it is assigned a value"),
+    ("E0302", "Invalid numeric constant '{}'"),
+    ("E0303", "Integer division by zero at instruction {}"),
+    (
+        "E0304",
+        "Reached the limit of {} executed instructions, possible infinite loop",
+    ),
+    (
+        "W0001",
+        "the variable '{}' declared at line {} column {} is never used",
+    ),
+    (
+        "W0002",
+        "the variable '{}' declared at line {} column {} is assigned a value that is never read",
+    ),
+    (
+        "W0003",
+        "the variable '{}' declared at line {} column {} is read but never assigned a value",
+    ),
+    ("warning-prefix", "Warning: {}"),
+    ("format-check", "'{}' is not in the canonical format"),
+    ("severity-error", "error"),
+    ("severity-warning", "warning"),
+    ("label-declared-here", "declared here"),
+    ("label-expected", "expected '{}'"),
+    ("expected-number", "integer or real number"),
+    ("expected-statement", "if, while or an assignment"),
+    ("variable-type-entero", "integer"),
+    ("variable-type-real", "real"),
+    ("token-tipo", "type"),
+    ("token-id", "Id"),
+    ("token-entero", "integer"),
+    ("token-real", "real"),
+    ("token-operador-arit-a", "+ or -"),
+    ("token-operador-arit-b", "/ or *"),
+    ("token-operador-condicion", "conditional operator"),
+    ("token-eof", "end of input"),
+    ("token-unknown", "Unrecognized"),
+    ("column-type", "Type"),
+    ("column-lexeme", "Lexeme"),
+    ("column-line", "Line"),
+    ("column-column", "Column"),
+    (
+        "usage",
+        "Usage: translator [command] [options] [file]
+
+Commands:
+    tokens    shows the tokens produced by the lexer
+    parse     shows the syntax tree
+    ir        shows the three-address code (default)
+    run       runs the program and shows the final value of the variables
+    check     only reports errors and warnings
+    fmt       prints the program in the canonical format
+
+Options:
+    --dot         with parse, prints the tree in Graphviz format
+    --dag-dot     with ir, prints the expression DAGs in Graphviz format
+    --cfg-dot     with ir, prints the basic blocks and their jumps in Graphviz format
+    --color       highlights errors and warnings with ANSI colors
+    --check       with fmt, only checks that the file is already in the canonical format
+    --split-decls with fmt, writes one variable per declaration
+    --json        prints errors and warnings as a JSON array
+    --lang LANG   language of the messages: es (default) or en
+    --tab-width N columns a tab advances in the positions (default 4)
+    -h, --help    shows this help
+
+The command can be given anywhere. Without a file, or with '-', the program is read
+from the standard input.",
+    ),
+    (
+        "cli-missing-language",
+        "Missing the language after '--lang'",
+    ),
+    ("cli-unknown-language", "Unknown language '{}'"),
+    (
+        "cli-missing-tab-width",
+        "Missing the width after '--tab-width'",
+    ),
+    (
+        "cli-invalid-tab-width",
+        "Invalid tab width '{}', it must be a positive integer",
+    ),
+    ("cli-unknown-option", "Unknown option '{}'"),
+    ("cli-unexpected-argument", "Unexpected argument '{}'"),
+    (
+        "cli-option-command",
+        "The option '{}' cannot be used with the command '{}'",
+    ),
+    (
+        "cli-conflicting-options",
+        "The options '{}' and '{}' cannot be used together",
+    ),
+    ("read-error", "Could not read '{}': {}"),
+    ("dot-leaf", "line {}, column {}"),
+    ("dot-assignment", "{} := (line {})"),
+    ("dot-condition", "condition {} (line {})"),
+    ("dot-left", "left"),
+    ("dot-right", "right"),
+    ("cfg-entry", "entry"),
+    ("cfg-exit", "exit"),
+    ("cfg-true", "true"),
+    ("cfg-false", "false"),
+];
+
+/// Text for `key` in the given locale, the key itself when the catalog has no entry for it
+pub fn text(key: &str, locale: Locale) -> &str {
+    let catalog = match locale {
+        Locale::Es => ES,
+        Locale::En => EN,
+    };
+    catalog
+        .iter()
+        .find(|(entry, _)| *entry == key)
+        .map(|(_, text)| *text)
+        .unwrap_or(key)
+}
+
+/// Fills the `{}` marks of the text for `key` with `arguments`, in order
+pub fn format(key: &str, locale: Locale, arguments: &[&dyn fmt::Display]) -> String {
+    let mut pieces = text(key, locale).split("{}");
+    let mut formatted = String::from(pieces.next().unwrap_or_default());
+    for (index, piece) in pieces.enumerate() {
+        if let Some(argument) = arguments.get(index) {
+            formatted.push_str(&argument.to_string());
+        }
+        formatted.push_str(piece);
+    }
+    formatted
+}
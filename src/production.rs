@@ -1,6 +1,10 @@
 use core::fmt;
 
-use crate::token::Token;
+use crate::{
+    dot::escape,
+    messages::{format, Locale},
+    token::Token,
+};
 
 #[derive(Debug, Clone)]
 pub enum ProductionItem {
@@ -8,7 +12,7 @@ pub enum ProductionItem {
     Production(Production),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ProductionType {
     Programa,
     Declaraciones,
@@ -21,6 +25,11 @@ pub enum ProductionType {
     Orden,
     Condicion,
     SigCondicion,
+    ExpresionLogica,
+    RestLogica,
+    TerminoLogico,
+    RestTerminoLogico,
+    FactorLogico,
     Comparacion,
     Operador,
     Numeros,
@@ -31,6 +40,7 @@ pub enum ProductionType {
     Term,
     RestTerm,
     Factor,
+    Error,
 }
 
 #[derive(Debug, Clone)]
@@ -69,13 +79,56 @@ impl Production {
             "{}├ {}{}{}",
             prepend,
             production_type_to_str(&self.production_type),
-            if self.items.len() > 0 { "\n" } else { "" },
+            if !self.items.is_empty() { "\n" } else { "" },
             joined,
         )
         .to_string()
     }
 }
 
+impl Production {
+    /// Graphviz representation of the tree, productions are labeled with their type and
+    /// leaves with their lexeme and position
+    pub fn to_dot(&self, locale: Locale) -> String {
+        let mut lines = vec![
+            String::from("digraph programa {"),
+            String::from("    node [fontname=\"monospace\"];"),
+        ];
+        let mut next_id = 0;
+        self.dot_nodes(&mut lines, &mut next_id, locale);
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+
+    fn dot_nodes(&self, lines: &mut Vec<String>, next_id: &mut usize, locale: Locale) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        lines.push(format!(
+            "    n{} [label=\"{}\"];",
+            id,
+            escape(&production_type_to_str(&self.production_type))
+        ));
+        for item in &self.items {
+            let child = match item {
+                ProductionItem::Production(prod) => prod.dot_nodes(lines, next_id, locale),
+                ProductionItem::Leaf(leaf) => {
+                    let child = *next_id;
+                    *next_id += 1;
+                    lines.push(format!(
+                        "    n{} [shape=box, label=\"{}\\n{}\"];",
+                        child,
+                        escape(&leaf.lexeme),
+                        format("dot-leaf", locale, &[&leaf.line, &leaf.col])
+                    ));
+                    child
+                }
+            };
+            lines.push(format!("    n{} -> n{};", id, child));
+        }
+        id
+    }
+}
+
 impl fmt::Display for Production {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_string(""))
@@ -95,6 +148,11 @@ pub fn production_type_to_str(production_type: &ProductionType) -> String {
         ProductionType::Orden => "orden",
         ProductionType::Condicion => "condicion",
         ProductionType::SigCondicion => "sig_condicion",
+        ProductionType::ExpresionLogica => "expresion_logica",
+        ProductionType::RestLogica => "rest_logica",
+        ProductionType::TerminoLogico => "termino_logico",
+        ProductionType::RestTerminoLogico => "rest_termino_logico",
+        ProductionType::FactorLogico => "factor_logico",
         ProductionType::Comparacion => "comparación",
         ProductionType::Operador => "operador",
         ProductionType::Numeros => "numeros",
@@ -105,6 +163,7 @@ pub fn production_type_to_str(production_type: &ProductionType) -> String {
         ProductionType::Term => "termino",
         ProductionType::RestTerm => "rest_term",
         ProductionType::Factor => "factor",
+        ProductionType::Error => "error",
     }
     .to_string()
 }
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use crate::{
+    dot::escape,
+    messages::{text, Locale},
+};
+
+use super::ir::{Code, Instruction, Label};
+
+/// Position of a block in its graph, in the order of the code
+pub type BlockId = usize;
+
+/// Instructions that always run one after the other: only the first one can be reached by a
+/// jump and only the last one can jump
+#[derive(Debug, Clone, Default)]
+pub struct BasicBlock {
+    pub instructions: Vec<Instruction>,
+    pub successors: Vec<BlockId>,
+    pub predecessors: Vec<BlockId>,
+}
+
+impl BasicBlock {
+    /// Whether a new label still belongs to this block, so consecutive labels share one
+    fn only_labels(&self) -> bool {
+        self.instructions
+            .iter()
+            .all(|instruction| matches!(instruction, Instruction::Label(_)))
+    }
+}
+
+/// Control-flow graph of three-address code, the first block is the entry of the program
+#[derive(Debug, Clone, Default)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl ControlFlowGraph {
+    /// Splits the code into basic blocks, a block starts at a label or right after a jump
+    pub fn from_code(code: &Code) -> Self {
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        let mut current = BasicBlock::default();
+        for instruction in &code.instructions {
+            if matches!(instruction, Instruction::Label(_)) && !current.only_labels() {
+                blocks.push(current);
+                current = BasicBlock::default();
+            }
+            current.instructions.push(instruction.clone());
+            if matches!(
+                instruction,
+                Instruction::Jump(_) | Instruction::CondJumpFalse { .. }
+            ) {
+                blocks.push(current);
+                current = BasicBlock::default();
+            }
+        }
+        if !current.instructions.is_empty() {
+            blocks.push(current);
+        }
+        let mut graph = ControlFlowGraph { blocks };
+        graph.connect();
+        graph
+    }
+
+    /// Fills the successors of every block from its last instruction, and the predecessors
+    /// from the successors
+    fn connect(&mut self) {
+        let mut labels: HashMap<Label, BlockId> = HashMap::new();
+        for (id, block) in self.blocks.iter().enumerate() {
+            for instruction in &block.instructions {
+                if let Instruction::Label(label) = instruction {
+                    labels.insert(*label, id);
+                }
+            }
+        }
+        let count = self.blocks.len();
+        for id in 0..count {
+            let fallthrough = (id + 1 < count).then_some(id + 1);
+            let successors: Vec<BlockId> = match self.blocks[id].instructions.last() {
+                Some(Instruction::Jump(target)) => {
+                    labels.get(target).copied().into_iter().collect()
+                }
+                // The target is taken when the condition is false, the next block otherwise
+                Some(Instruction::CondJumpFalse { target, .. }) => {
+                    let mut successors: Vec<BlockId> = fallthrough.into_iter().collect();
+                    if let Some(target) = labels.get(target) {
+                        if !successors.contains(target) {
+                            successors.push(*target);
+                        }
+                    }
+                    successors
+                }
+                _ => fallthrough.into_iter().collect(),
+            };
+            for successor in &successors {
+                self.blocks[*successor].predecessors.push(id);
+            }
+            self.blocks[id].successors = successors;
+        }
+    }
+
+    /// Label of an edge, conditional jumps tell apart the branch taken when the condition holds
+    fn edge_label(&self, from: BlockId, to: BlockId, locale: Locale) -> Option<&'static str> {
+        match self.blocks[from].instructions.last() {
+            Some(Instruction::CondJumpFalse { .. }) if self.blocks[from].successors.len() > 1 => {
+                Some(match to == from + 1 {
+                    true => text("cfg-true", locale),
+                    false => text("cfg-false", locale),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Graphviz graph with a box per block listing its instructions, plus entry and exit nodes
+    pub fn to_dot(&self, locale: Locale) -> String {
+        let mut lines = vec![
+            String::from("digraph cfg {"),
+            String::from("    node [shape=box];"),
+            format!(
+                "    inicio [shape=oval, label=\"{}\"];",
+                text("cfg-entry", locale)
+            ),
+            format!(
+                "    fin [shape=oval, label=\"{}\"];",
+                text("cfg-exit", locale)
+            ),
+        ];
+        for (id, block) in self.blocks.iter().enumerate() {
+            let mut label = format!("B{}\\l", id);
+            for instruction in &block.instructions {
+                label.push_str(&escape(&instruction.to_string()));
+                label.push_str("\\l");
+            }
+            lines.push(format!("    b{} [label=\"{}\"];", id, label));
+        }
+        lines.push(match self.blocks.is_empty() {
+            true => String::from("    inicio -> fin;"),
+            false => String::from("    inicio -> b0;"),
+        });
+        for (id, block) in self.blocks.iter().enumerate() {
+            for successor in &block.successors {
+                lines.push(match self.edge_label(id, *successor, locale) {
+                    Some(label) => format!("    b{} -> b{} [label=\"{}\"];", id, successor, label),
+                    None => format!("    b{} -> b{};", id, successor),
+                });
+            }
+            // The last block leaves the program unless it ends jumping back
+            let jumps = matches!(block.instructions.last(), Some(Instruction::Jump(_)));
+            if id + 1 == self.blocks.len() && !jumps {
+                lines.push(format!("    b{} -> fin;", id));
+            }
+        }
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{ir::Operand, ir::RelationalOperator, SemanticAnalyzer},
+        *,
+    };
+
+    /// Graph of the sample program bundled with the crate
+    fn sample() -> ControlFlowGraph {
+        let code = SemanticAnalyzer::new()
+            .parse_all(include_str!("../../test"))
+            .expect("the sample program compiles");
+        ControlFlowGraph::from_code(&code)
+    }
+
+    fn assign(variable: &str, value: &str) -> Instruction {
+        Instruction::Assign {
+            variable: String::from(variable),
+            value: Operand::Constant(String::from(value)),
+        }
+    }
+
+    /// Successors and predecessors of every block, in order
+    fn edges(graph: &ControlFlowGraph) -> Vec<(Vec<BlockId>, Vec<BlockId>)> {
+        graph
+            .blocks
+            .iter()
+            .map(|block| (block.successors.clone(), block.predecessors.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn the_sample_program_splits_at_labels_and_jumps() {
+        let graph = sample();
+        assert_eq!(
+            edges(&graph),
+            vec![
+                (vec![1, 2], vec![]),
+                (vec![3], vec![0]),
+                (vec![3], vec![0]),
+                (vec![4, 5], vec![1, 2, 4]),
+                (vec![3], vec![3]),
+                (vec![], vec![3]),
+            ]
+        );
+        // The end of the if and the start of the while are one block
+        assert_eq!(
+            graph.blocks[3].instructions[..2],
+            [Instruction::Label(Label(2)), Instruction::Label(Label(3))]
+        );
+    }
+
+    #[test]
+    fn the_sample_program_labels_the_branches_of_its_conditions() {
+        let dot = sample().to_dot(Locale::En);
+        let edges: Vec<&str> = dot
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.contains("->"))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                "inicio -> b0;",
+                "b0 -> b1 [label=\"true\"];",
+                "b0 -> b2 [label=\"false\"];",
+                "b1 -> b3;",
+                "b2 -> b3;",
+                "b3 -> b4 [label=\"true\"];",
+                "b3 -> b5 [label=\"false\"];",
+                "b4 -> b3;",
+                "b5 -> fin;",
+            ]
+        );
+    }
+
+    #[test]
+    fn a_jump_to_the_next_block_is_a_single_edge() {
+        let code = Code::from(vec![
+            Instruction::Label(Label(1)),
+            Instruction::Label(Label(2)),
+            assign("a", "1"),
+            Instruction::CondJumpFalse {
+                left: Operand::Variable(String::from("a")),
+                op: RelationalOperator::Less,
+                right: Operand::Constant(String::from("1")),
+                target: Label(3),
+            },
+            Instruction::Label(Label(3)),
+            assign("b", "2"),
+            Instruction::Jump(Label(4)),
+            Instruction::Label(Label(4)),
+            assign("c", "3"),
+            Instruction::Jump(Label(1)),
+        ]);
+        let graph = ControlFlowGraph::from_code(&code);
+        assert_eq!(graph.blocks.len(), 3);
+        assert_eq!(graph.blocks[0].instructions.len(), 4);
+        assert_eq!(
+            edges(&graph),
+            vec![(vec![1], vec![2]), (vec![2], vec![0]), (vec![0], vec![1])]
+        );
+        let dot = graph.to_dot(Locale::En);
+        assert!(dot.contains("    b0 -> b1;\n"));
+        assert!(dot.contains("    b2 -> b0;\n"));
+        // The program never leaves the loop
+        assert!(!dot.contains("-> fin"));
+    }
+
+    #[test]
+    fn empty_code_goes_from_the_entry_to_the_exit() {
+        let graph = ControlFlowGraph::from_code(&Code::default());
+        assert!(graph.blocks.is_empty());
+        assert!(graph.to_dot(Locale::Es).contains("    inicio -> fin;\n"));
+    }
+}
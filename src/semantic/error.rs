@@ -1,17 +1,24 @@
 use std::{error, fmt};
 
 use crate::{
+    lexic::error::LexicError,
+    messages::{format, Locale},
     production::{production_type_to_str, Production},
     sintactic::error::SintacticError,
-    token::Token,
+    symbols::{variable_type_to_str, VariableType},
+    token::{token_to_str, Token},
 };
 
 #[derive(Debug, Clone)]
 pub enum SemanticErrorType {
+    Lexic(LexicError),
     Sintactic(SintacticError),
     Undefined(Token),
     BadFormat(Production),
     Unexpected(Token),
+    Narrowing(Token, VariableType, VariableType),
+    Redeclared(Token, Token),
+    DivisionByZero(Token),
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +27,12 @@ pub struct SemanticError {
 }
 
 impl SemanticError {
+    pub fn from_lexic(error: LexicError) -> Self {
+        SemanticError {
+            error_type: SemanticErrorType::Lexic(error),
+        }
+    }
+
     pub fn from_sintactic(error: SintacticError) -> Self {
         SemanticError {
             error_type: SemanticErrorType::Sintactic(error),
@@ -42,27 +55,107 @@ impl SemanticError {
             error_type: SemanticErrorType::Unexpected(token),
         }
     }
+
+    pub fn from_redeclared(token: Token, previous: Token) -> Self {
+        SemanticError {
+            error_type: SemanticErrorType::Redeclared(token, previous),
+        }
+    }
+
+    pub fn from_division(token: Token) -> Self {
+        SemanticError {
+            error_type: SemanticErrorType::DivisionByZero(token),
+        }
+    }
+
+    pub fn from_narrowing(token: Token, declared: VariableType, found: VariableType) -> Self {
+        SemanticError {
+            error_type: SemanticErrorType::Narrowing(token, declared, found),
+        }
+    }
 }
-impl error::Error for SemanticError {}
-impl fmt::Display for SemanticError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl SemanticError {
+    pub fn error_type(&self) -> &SemanticErrorType {
+        &self.error_type
+    }
+
+    pub fn code(&self) -> &'static str {
         match &self.error_type {
-            SemanticErrorType::BadFormat(prod) => write!(
-                f,
-                "Producción con formato inesperado {}",
-                production_type_to_str(&prod.production_type)
+            SemanticErrorType::Lexic(lexic) => lexic.code(),
+            SemanticErrorType::Sintactic(sintactic) => sintactic.code(),
+            SemanticErrorType::Undefined(_) => "E0200",
+            SemanticErrorType::BadFormat(_) => "E0201",
+            SemanticErrorType::Unexpected(_) => "E0202",
+            SemanticErrorType::Narrowing(_, _, _) => "E0203",
+            SemanticErrorType::Redeclared(_, _) => "E0204",
+            SemanticErrorType::DivisionByZero(_) => "E0205",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        let code = self.code();
+        match &self.error_type {
+            SemanticErrorType::BadFormat(prod) => format(
+                code,
+                locale,
+                &[&production_type_to_str(&prod.production_type)],
             ),
-            SemanticErrorType::Undefined(token) => writeln!(
-                f,
-                "Utilizacion de una variable no declarada '{}' en la linea {} columna {}",
-                token.lexeme, token.line, token.col
+            SemanticErrorType::Undefined(token) => {
+                format(code, locale, &[&token.lexeme, &token.line, &token.col])
+            }
+            SemanticErrorType::Unexpected(token) => format(
+                code,
+                locale,
+                &[&token_to_str(token, locale), &token.line, &token.col],
             ),
-            SemanticErrorType::Unexpected(token) => writeln!(
-                f,
-                "Caracter inesperado '{}' en la linea {} columna {}",
-                token.lexeme, token.line, token.col
+            SemanticErrorType::DivisionByZero(token) => {
+                format(code, locale, &[&token.line, &token.col])
+            }
+            SemanticErrorType::Narrowing(token, declared, found) => format(
+                code,
+                locale,
+                &[
+                    &variable_type_to_str(found, locale),
+                    &variable_type_to_str(declared, locale),
+                    &token.lexeme,
+                    &token.line,
+                    &token.col,
+                ],
             ),
-            SemanticErrorType::Sintactic(sintactic) => sintactic.fmt(f),
+            SemanticErrorType::Redeclared(token, previous) => format(
+                code,
+                locale,
+                &[
+                    &token.lexeme,
+                    &token.line,
+                    &token.col,
+                    &previous.line,
+                    &previous.col,
+                ],
+            ),
+            SemanticErrorType::Lexic(lexic) => lexic.message(locale),
+            SemanticErrorType::Sintactic(sintactic) => sintactic.message(locale),
+        }
+    }
+
+    /// Token the error points at, if it has one
+    pub fn token(&self) -> Option<&Token> {
+        match &self.error_type {
+            SemanticErrorType::Lexic(lexic) => Some(&lexic.token),
+            SemanticErrorType::Sintactic(sintactic) => Some(sintactic.token()),
+            SemanticErrorType::Undefined(token)
+            | SemanticErrorType::Unexpected(token)
+            | SemanticErrorType::Narrowing(token, _, _)
+            | SemanticErrorType::Redeclared(token, _)
+            | SemanticErrorType::DivisionByZero(token) => Some(token),
+            SemanticErrorType::BadFormat(_) => None,
         }
     }
 }
+
+impl error::Error for SemanticError {}
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Locale::default()))
+    }
+}
@@ -1,76 +1,75 @@
-use crate::{production::Production, symbols::SymbolsTable};
+use std::collections::HashMap;
+
+use crate::{
+    ast::Expr,
+    symbols::{SymbolsTable, VariableType},
+    token::Token,
+};
 
 use super::{
     error::SemanticError,
-    graph::{Graph, Node},
-    utils::{production_as_leaf, production_as_node},
+    graph::{Graph, Node, NodeId},
 };
 
 pub struct ExpressionAnalyzer {
     symbols_table: SymbolsTable,
     pub graph: Graph,
+    /// First operator token that produced each operation node, to report errors found later
+    operators: HashMap<NodeId, Token>,
 }
 
 pub type ExpressionResult = Result<Graph, SemanticError>;
-pub type IntermediateResult = Result<u64, SemanticError>;
+pub type IntermediateResult = Result<NodeId, SemanticError>;
 
 impl ExpressionAnalyzer {
     pub fn from(table: &SymbolsTable) -> Self {
         ExpressionAnalyzer {
             symbols_table: table.clone(),
             graph: Graph::new(),
+            operators: HashMap::new(),
         }
     }
 
-    pub fn numeros(&mut self, prod: &Production) -> IntermediateResult {
-        let token = production_as_leaf(&prod.items[0])?;
-        Ok(self.graph.add(Node::from_num(&token)))
-    }
-
-    pub fn operador(&mut self, prod: &Production) -> IntermediateResult {
-        if let Ok(token) = production_as_leaf(&prod.items[0]) {
-            return match self.symbols_table.get_hash_if_set(&token) {
-                Some(hash) => Ok(self.graph.add(Node::from_var(&token, hash))),
-                None => Err(SemanticError::from_undefined(token.clone())),
-            };
-        }
-        self.numeros(production_as_node(&prod.items[0])?)
-    }
-
-    pub fn factor(&mut self, prod: &Production) -> IntermediateResult {
-        if let Ok(_) = production_as_leaf(&prod.items[0]) {
-            return self.expresion_arit(production_as_node(&prod.items[1])?);
+    pub fn variable(&mut self, token: &Token) -> IntermediateResult {
+        match self.symbols_table.get_from_token(token) {
+            Some(variable) => {
+                let node = Node::from_var(token, variable.index, &variable.variable_type);
+                Ok(self.graph.add(node))
+            }
+            None => Err(SemanticError::from_undefined(token.clone())),
         }
-        return self.operador(production_as_node(&prod.items[0])?);
     }
 
-    pub fn rest_term(&mut self, prod: &Production, previous: u64) -> IntermediateResult {
-        if prod.items.is_empty() {
-            return Ok(previous);
+    /// Adds the nodes of the expression to the DAG, returning the id of its root
+    pub fn expresion(&mut self, expr: &Expr) -> IntermediateResult {
+        match expr {
+            Expr::Num(token) => Ok(self.graph.add(Node::from_num(token))),
+            Expr::Var(token) => self.variable(token),
+            Expr::Bin { op, left, right } => {
+                let left = self.expresion(left)?;
+                let right = self.expresion(right)?;
+                let node_type = self.graph.combined_type(left, right);
+                let id = self.graph.add(Node::from_op(op, left, right, node_type));
+                self.operators.entry(id).or_insert_with(|| op.clone());
+                Ok(id)
+            }
         }
-        let op = production_as_leaf(&prod.items[0])?;
-        let factor = self.factor(production_as_node(&prod.items[1])?)?;
-        let rest = self.rest_term(production_as_node(&prod.items[2])?, factor)?;
-        Ok(self.graph.add(Node::from_op(op, previous, rest)))
     }
 
-    pub fn termino(&mut self, prod: &Production) -> IntermediateResult {
-        let factor = self.factor(production_as_node(&prod.items[0])?)?;
-        self.rest_term(production_as_node(&prod.items[1])?, factor)
-    }
-
-    pub fn rest_expr(&mut self, prod: &Production, previous: u64) -> IntermediateResult {
-        if prod.items.is_empty() {
-            return Ok(previous);
-        }
-        let op = production_as_leaf(&prod.items[0])?;
-        let factor = self.termino(production_as_node(&prod.items[1])?)?;
-        let rest = self.rest_expr(production_as_node(&prod.items[2])?, factor)?;
-        Ok(self.graph.add(Node::from_op(op, previous, rest)))
+    /// Replaces the graph with its simplified form, returning the nodes `roots` became
+    pub fn simplify(&mut self, roots: &[NodeId]) -> Result<Vec<NodeId>, SemanticError> {
+        let (graph, roots) = self.graph.simplify(roots).map_err(|id| {
+            let token = self.operators.get(&id).cloned().unwrap_or_default();
+            SemanticError::from_division(token)
+        })?;
+        self.graph = graph;
+        Ok(roots)
     }
 
-    pub fn expresion_arit(&mut self, prod: &Production) -> IntermediateResult {
-        let term = self.termino(production_as_node(&prod.items[0])?)?;
-        self.rest_expr(production_as_node(&prod.items[1])?, term)
+    pub fn expression_type(&self, id: NodeId) -> VariableType {
+        self.graph
+            .node_type(id)
+            .cloned()
+            .unwrap_or(VariableType::Real)
     }
 }
@@ -1,19 +1,24 @@
-use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
-    hash::{Hash, Hasher},
-};
+use std::collections::HashMap;
 
-use crate::token::{Token, TokenType};
+use crate::{
+    dot::escape,
+    messages::{format, text, Locale},
+    symbols::{SymbolsTable, VariableId, VariableType},
+    token::{Token, TokenType},
+};
 
 use super::utils::append_id;
 
-#[derive(Debug, Clone, Hash)]
+/// Leaf for a number or variable, or a binary operation. A variable leaf keeps the id of the
+/// variable in `left`, an operation keeps the ids of its operands in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Node {
     pub op: TokenType,
     pub lexeme: String,
     pub is_leaf: bool,
-    pub left: u64,
-    pub right: u64,
+    pub left: usize,
+    pub right: usize,
+    pub node_type: VariableType,
 }
 
 impl Node {
@@ -24,72 +29,167 @@ impl Node {
             is_leaf: true,
             left: 0,
             right: 0,
+            node_type: match token.token_type {
+                TokenType::Real => VariableType::Real,
+                _ => VariableType::Entero,
+            },
+        }
+    }
+
+    /// Number leaf for a value computed at compile time
+    pub fn from_constant(lexeme: &str, node_type: VariableType) -> Self {
+        Node {
+            op: match node_type {
+                VariableType::Entero => TokenType::Entero,
+                VariableType::Real => TokenType::Real,
+            },
+            lexeme: lexeme.to_string(),
+            is_leaf: true,
+            left: 0,
+            right: 0,
+            node_type,
         }
     }
 
-    pub fn from_var(token: &Token, hash: u64) -> Self {
+    pub fn from_var(token: &Token, id: VariableId, variable_type: &VariableType) -> Self {
         Node {
             op: token.token_type.clone(),
             lexeme: append_id(&token.lexeme),
             is_leaf: true,
-            left: hash,
+            left: id,
             right: 0,
+            node_type: variable_type.clone(),
         }
     }
 
-    pub fn from_op(token: &Token, left: u64, right: u64) -> Self {
+    pub fn from_op(token: &Token, left: NodeId, right: NodeId, node_type: VariableType) -> Self {
         Node {
             op: token.token_type.clone(),
             lexeme: token.lexeme.clone(),
             is_leaf: false,
             left,
             right,
+            node_type,
         }
     }
-
-    pub fn get_hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        self.hash(&mut hasher);
-        hasher.finish()
-    }
 }
 
-#[derive(Debug, Clone)]
-pub struct NodeWithIndex {
-    pub node: Node,
-    pub index: usize,
-}
+/// Position of a node in its graph, children always come before their parents
+pub type NodeId = usize;
 
+/// Expression DAG. Nodes are interned by comparing their whole structure, so two different
+/// subexpressions never share an id.
 #[derive(Debug, Clone, Default)]
 pub struct Graph {
-    pub table: HashMap<u64, NodeWithIndex>,
-    pub stack: Vec<u64>,
-}
-
-impl NodeWithIndex {
-    pub fn new(node: Node, index: usize) -> Self {
-        NodeWithIndex { index, node }
-    }
+    pub nodes: Vec<Node>,
+    pub ids: HashMap<Node, NodeId>,
 }
 
 impl Graph {
     pub fn new() -> Self {
         Graph {
-            table: HashMap::new(),
-            stack: Vec::new(),
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    /// Adds the node unless an equal one exists, returning the id of the node in the graph
+    pub fn add(&mut self, node: Node) -> NodeId {
+        if let Some(id) = self.ids.get(&node) {
+            return *id;
         }
+        let id = self.nodes.len();
+        self.ids.insert(node.clone(), id);
+        self.nodes.push(node);
+        id
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    pub fn node_type(&self, id: NodeId) -> Option<&VariableType> {
+        self.get(id).map(|node| &node.node_type)
     }
-    pub fn add(&mut self, node: Node) -> u64 {
-        let hash = node.get_hash();
-        if let None = self.table.get(&hash) {
-            let node_with_index = NodeWithIndex::new(node.clone(), self.stack.len());
-            self.table.insert(hash, node_with_index);
-            self.stack.push(hash);
+
+    /// Type of combining two existing nodes, entero operands widen to real when mixed
+    pub fn combined_type(&self, left: NodeId, right: NodeId) -> VariableType {
+        match (self.node_type(left), self.node_type(right)) {
+            (Some(left), Some(right)) => VariableType::widen(left, right),
+            _ => VariableType::Real,
+        }
+    }
+
+    /// Label of a node, variable leaves are resolved back to their name in the symbols table
+    pub fn dot_label(node: &Node, table: &SymbolsTable) -> String {
+        match (node.is_leaf, &node.op) {
+            (true, TokenType::Id) => match table.get(node.left) {
+                Some(variable) => variable.token.lexeme.clone(),
+                None => node.lexeme.clone(),
+            },
+            _ => node.lexeme.clone(),
         }
-        hash
     }
 
-    pub fn get(&self, hash: &u64) -> Option<&NodeWithIndex> {
-        self.table.get(hash)
+    /// Nodes and edges of the DAG as Graphviz statements, node ids are prefixed with `prefix`
+    pub fn dot_statements(
+        &self,
+        prefix: &str,
+        table: &SymbolsTable,
+        locale: Locale,
+    ) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (id, node) in self.nodes.iter().enumerate() {
+            lines.push(format!(
+                "{}{} [{}label=\"{}\"];",
+                prefix,
+                id,
+                if node.is_leaf { "shape=box, " } else { "" },
+                escape(&Graph::dot_label(node, table))
+            ));
+            if node.is_leaf {
+                continue;
+            }
+            for (child, side) in [(node.left, "dot-left"), (node.right, "dot-right")] {
+                lines.push(format!(
+                    "{}{} -> {}{} [label=\"{}\"];",
+                    prefix,
+                    id,
+                    prefix,
+                    child,
+                    text(side, locale)
+                ));
+            }
+        }
+        lines
+    }
+
+    pub fn to_dot(&self, table: &SymbolsTable, locale: Locale) -> String {
+        let mut lines = vec![String::from("digraph expresion {")];
+        for statement in self.dot_statements("n", table, locale) {
+            lines.push(format!("    {}", statement));
+        }
+        lines.push(String::from("}"));
+        lines.join("\n")
+    }
+}
+
+/// Every expression DAG of a program in one Graphviz graph, with a cluster per statement.
+/// Each graph is paired with the assigned identifier or the relational operator it feeds.
+pub fn graphs_to_dot(graphs: &[(Token, Graph)], table: &SymbolsTable, locale: Locale) -> String {
+    let mut lines = vec![String::from("digraph expresiones {")];
+    for (index, (token, graph)) in graphs.iter().enumerate() {
+        let label = match token.token_type {
+            TokenType::Id => format("dot-assignment", locale, &[&token.lexeme, &token.line]),
+            _ => format("dot-condition", locale, &[&token.lexeme, &token.line]),
+        };
+        lines.push(format!("    subgraph cluster_{} {{", index));
+        lines.push(format!("        label=\"{}\";", escape(&label)));
+        for statement in graph.dot_statements(&format!("g{}_", index), table, locale) {
+            lines.push(format!("        {}", statement));
+        }
+        lines.push(String::from("    }"));
     }
+    lines.push(String::from("}"));
+    lines.join("\n")
 }
@@ -0,0 +1,216 @@
+use core::fmt;
+
+use super::utils::append_id;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOperator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOperator {
+    pub fn from_lexeme(lexeme: &str) -> Option<Self> {
+        match lexeme {
+            "+" => Some(BinaryOperator::Add),
+            "-" => Some(BinaryOperator::Sub),
+            "*" => Some(BinaryOperator::Mul),
+            "/" => Some(BinaryOperator::Div),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RelationalOperator {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl RelationalOperator {
+    pub fn from_lexeme(lexeme: &str) -> Option<Self> {
+        match lexeme {
+            "=" => Some(RelationalOperator::Equal),
+            "<>" => Some(RelationalOperator::NotEqual),
+            "<" => Some(RelationalOperator::Less),
+            "<=" => Some(RelationalOperator::LessEqual),
+            ">" => Some(RelationalOperator::Greater),
+            ">=" => Some(RelationalOperator::GreaterEqual),
+            _ => None,
+        }
+    }
+
+    /// Operator that holds exactly when this one does not
+    pub fn negate(&self) -> Self {
+        match self {
+            RelationalOperator::Equal => RelationalOperator::NotEqual,
+            RelationalOperator::NotEqual => RelationalOperator::Equal,
+            RelationalOperator::Less => RelationalOperator::GreaterEqual,
+            RelationalOperator::LessEqual => RelationalOperator::Greater,
+            RelationalOperator::Greater => RelationalOperator::LessEqual,
+            RelationalOperator::GreaterEqual => RelationalOperator::Less,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationalOperator::Equal => "=",
+            RelationalOperator::NotEqual => "<>",
+            RelationalOperator::Less => "<",
+            RelationalOperator::LessEqual => "<=",
+            RelationalOperator::Greater => ">",
+            RelationalOperator::GreaterEqual => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Variable(String),
+    Temporary(u32),
+    Constant(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label(pub u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// `_var := operand`
+    Assign { variable: String, value: Operand },
+    /// `__temp_N := left op right`
+    BinOp {
+        temp: u32,
+        left: Operand,
+        op: BinaryOperator,
+        right: Operand,
+    },
+    /// `jmp_N:`
+    Label(Label),
+    /// `jump to jmp_N`
+    Jump(Label),
+    /// `if false left op right jump to jmp_N`
+    CondJumpFalse {
+        left: Operand,
+        op: RelationalOperator,
+        right: Operand,
+        target: Label,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Code {
+    pub instructions: Vec<Instruction>,
+}
+
+impl Code {
+    pub fn new() -> Self {
+        Code {
+            instructions: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, instruction: Instruction) {
+        self.instructions.push(instruction);
+    }
+
+    pub fn append(&mut self, mut code: Code) {
+        self.instructions.append(&mut code.instructions);
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+}
+
+impl From<Vec<Instruction>> for Code {
+    fn from(instructions: Vec<Instruction>) -> Self {
+        Code { instructions }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for RelationalOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Variable(name) => write!(f, "{}", append_id(name)),
+            Operand::Temporary(index) => write!(f, "__temp_{}", index),
+            Operand::Constant(lexeme) => write!(f, "{}", lexeme),
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "jmp_{}", self.0)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Assign { variable, value } => {
+                write!(f, "{} := {}", append_id(variable), value)
+            }
+            Instruction::BinOp {
+                temp,
+                left,
+                op,
+                right,
+            } => write!(
+                f,
+                "{} := {} {} {}",
+                Operand::Temporary(*temp),
+                left,
+                op,
+                right
+            ),
+            Instruction::Label(label) => write!(f, "{}:", label),
+            Instruction::Jump(label) => write!(f, "jump to {}", label),
+            Instruction::CondJumpFalse {
+                left,
+                op,
+                right,
+                target,
+            } => write!(f, "if false {} {} {} jump to {}", left, op, right, target),
+        }
+    }
+}
+
+impl fmt::Display for Code {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
@@ -1,267 +1,472 @@
+pub mod cfg;
 pub mod error;
 pub mod expresion;
 pub mod graph;
+pub mod ir;
+pub mod optimize;
+pub mod usage;
 pub mod utils;
+pub mod warning;
 
 use crate::{
-    production::{Production, ProductionItem, ProductionType},
+    ast::{lower::lower, Cond, Decl, Expr, Program, Stmt},
+    lexic::DEFAULT_TAB_WIDTH,
     sintactic::SintacticAnalyzer,
     symbols::{SymbolsTable, VariableType},
-    token::TokenType,
+    token::{Token, TokenType},
 };
 
 use self::{
     error::SemanticError,
     expresion::ExpressionAnalyzer,
-    utils::{append_id, production_as_leaf, production_as_node},
+    graph::Graph,
+    ir::{BinaryOperator, Code, Instruction, Label, Operand, RelationalOperator},
+    optimize::value_numbering,
+    usage::UsageAnalyzer,
+    warning::SemanticWarning,
 };
 
 pub struct SemanticAnalyzer {
     pub table: SymbolsTable,
+    pub warnings: Vec<SemanticWarning>,
+    pub graphs: Vec<(Token, Graph)>,
+    /// Width of a tab in the columns of the tokens
+    pub tab_width: usize,
     current_jump: u32,
     current_temp: u32,
 }
 
-pub type SemanticRepresentation = String;
+pub type SemanticRepresentation = Code;
 pub type SemanticResult = Result<SemanticRepresentation, SemanticError>;
 
+impl Default for SemanticAnalyzer {
+    fn default() -> Self {
+        SemanticAnalyzer::new()
+    }
+}
+
 impl SemanticAnalyzer {
     pub fn new() -> Self {
+        SemanticAnalyzer::with_tab_width(DEFAULT_TAB_WIDTH)
+    }
+
+    /// Analyzer whose positions treat a tab as advancing to the next multiple of `tab_width`
+    pub fn with_tab_width(tab_width: usize) -> Self {
         SemanticAnalyzer {
             table: SymbolsTable::new(),
+            warnings: Vec::new(),
+            graphs: Vec::new(),
+            tab_width,
             current_jump: 0,
             current_temp: 0,
         }
     }
 
-    pub fn next_jump(&mut self) -> String {
+    pub fn next_jump(&mut self) -> Label {
         self.current_jump += 1;
-        let tag = format!("jmp_{}", self.current_jump);
-        tag
+        Label(self.current_jump)
     }
 
-    pub fn next_temp(&mut self) -> String {
+    pub fn next_temp(&mut self) -> u32 {
         self.current_temp += 1;
-        let tag = format!("__temp_{}", self.current_temp);
-        tag
+        self.current_temp
     }
 
-    pub fn current_temp(&mut self) -> String {
-        let tag = format!("__temp_{}", self.current_temp);
-        tag
+    pub fn current_temp(&self) -> Operand {
+        Operand::Temporary(self.current_temp)
     }
 
     pub fn parse(&mut self, input: &str) -> SemanticResult {
+        self.parse_all(input)
+            .map_err(|errors| errors.into_iter().next().unwrap())
+    }
+
+    /// Like `parse`, but reports every syntax error found in the input instead of the first one
+    pub fn parse_all(&mut self, input: &str) -> Result<SemanticRepresentation, Vec<SemanticError>> {
         self.table.clear();
+        self.warnings.clear();
+        self.graphs.clear();
         self.current_temp = 0;
         self.current_jump = 0;
 
         let input_cloned = input.to_string();
-        let mut sintactic_analyzer = SintacticAnalyzer::new(input_cloned.as_str());
-
-        let tree = match sintactic_analyzer.analize() {
-            Err(sintactic_error) => Err(SemanticError::from_sintactic(sintactic_error)),
-            Ok(sintactic_tree) => Ok(sintactic_tree),
-        }?;
-        self.declaraciones(production_as_node(&tree.items[1])?)?;
-        self.ordenes(production_as_node(&tree.items[2])?)
+        let mut sintactic_analyzer =
+            SintacticAnalyzer::with_tab_width(input_cloned.as_str(), self.tab_width);
+
+        let (tree, errors) = sintactic_analyzer.analize_all();
+        let mut errors: Vec<SemanticError> = sintactic_analyzer
+            .lexic
+            .errors
+            .iter()
+            .cloned()
+            .map(SemanticError::from_lexic)
+            .chain(errors.into_iter().map(SemanticError::from_sintactic))
+            .collect();
+        if !errors.is_empty() {
+            errors.sort_by_key(|error| error.token().map(|token| (token.line, token.col)));
+            return Err(errors);
+        }
+        let program = lower(&tree).map_err(|error| vec![error])?;
+        self.translate(&program).map_err(|error| vec![error])
     }
 
-    pub fn sig_lista_variables(
-        &mut self,
-        var_type: &VariableType,
-        lista: &Production,
-    ) -> Result<(), SemanticError> {
-        if lista.items.len() > 0 {
-            return self.lista_variables(var_type, production_as_node(&lista.items[1])?);
+    fn translate(&mut self, program: &Program) -> SemanticResult {
+        self.declaraciones(&program.declarations)?;
+        let code = value_numbering(&self.ordenes(&program.statements)?);
+        let mut usage = UsageAnalyzer::new();
+        usage.walk(&program.statements);
+        self.warnings = usage.warnings(&self.table);
+        Ok(code)
+    }
+
+    pub fn declaraciones(&mut self, declarations: &[Decl]) -> Result<(), SemanticError> {
+        for declaration in declarations {
+            for name in &declaration.names {
+                if let Err(previous) = self.table.add(name, &declaration.variable_type) {
+                    return Err(SemanticError::from_redeclared(name.clone(), previous.token));
+                }
+            }
         }
         Ok(())
     }
 
-    pub fn lista_variables(
+    /// Lowers both sides of a comparison through a single expression DAG, so each side is
+    /// computed into its own temporary before the conditional jump
+    pub fn comparacion(
         &mut self,
-        var_type: &VariableType,
-        lista: &Production,
-    ) -> Result<(), SemanticError> {
-        let id = production_as_leaf(&lista.items[0])?;
-        let next_list = production_as_node(&lista.items[1])?;
-        self.table.add(&id, var_type);
-        self.sig_lista_variables(var_type, next_list)
+        left: &Expr,
+        op_token: &Token,
+        right: &Expr,
+        target: Label,
+        jump_when: bool,
+    ) -> SemanticResult {
+        let mut analyzer = ExpressionAnalyzer::from(&self.table);
+        let left = analyzer.expresion(left)?;
+        let right = analyzer.expresion(right)?;
+        let roots = analyzer.simplify(&[left, right])?;
+        let (left, right) = (roots[0], roots[1]);
+        let op = RelationalOperator::from_lexeme(&op_token.lexeme)
+            .ok_or_else(|| SemanticError::from_unexpected(op_token.clone()))?;
+        self.graphs.push((op_token.clone(), analyzer.graph.clone()));
+        let (mut res, operands) = self.emit(&analyzer.graph)?;
+        res.push(Instruction::CondJumpFalse {
+            left: operands[left].clone(),
+            op: if jump_when { op.negate() } else { op },
+            right: operands[right].clone(),
+            target,
+        });
+        Ok(res)
     }
 
-    pub fn declaracion(&mut self, production: &Production) -> Result<(), SemanticError> {
-        let tipo = production_as_leaf(&production.items[0])?;
-        let lista = production_as_node(&production.items[1])?;
-        let var_type = match tipo.lexeme.as_str() {
-            "entero" => VariableType::Entero,
-            "real" => VariableType::Real,
-            _ => VariableType::Real,
+    /// Short-circuit code for a chain of `and` (`conjunction`) or `or` operands
+    pub fn cadena_logica(
+        &mut self,
+        operands: &[Cond],
+        target: Label,
+        jump_when: bool,
+        conjunction: bool,
+    ) -> SemanticResult {
+        let mut res = Code::new();
+        let (last, rest) = match operands.split_last() {
+            Some(split) => split,
+            None => return Ok(res),
         };
-        self.lista_variables(&var_type, &lista)
-    }
-
-    pub fn declaraciones(&mut self, production: &Production) -> Result<(), SemanticError> {
-        if production.items.len() == 3 {
-            let declaracion = production_as_node(&production.items[0])?;
-            let sig_declaraciones = production_as_node(&production.items[2])?;
-            self.declaracion(&declaracion)?;
-            self.declaraciones(&sig_declaraciones)?;
+        // A false operand decides an `and`, a true one decides an `or`
+        if conjunction != jump_when || rest.is_empty() {
+            for operand in operands {
+                res.append(self.logica(operand, target, jump_when)?);
+            }
+            return Ok(res);
         }
-        Ok(())
+        let skip = self.next_jump();
+        for operand in rest {
+            res.append(self.logica(operand, skip, !jump_when)?);
+        }
+        res.append(self.logica(last, target, jump_when)?);
+        res.push(Instruction::Label(skip));
+        Ok(res)
     }
 
-    pub fn operador(&mut self, production: &Production) -> SemanticResult {
-        let operador = &production.items[0];
-        match operador {
-            ProductionItem::Leaf(op) => Ok(append_id(&op.lexeme)),
-            ProductionItem::Production(num) => {
-                if let ProductionItem::Leaf(num) = &num.items[0] {
-                    Ok(num.lexeme.clone())
-                } else {
-                    Ok(String::new())
-                }
+    /// Jumping code for a condition: control reaches `target` when the condition evaluates to
+    /// `jump_when` and falls through to the next instruction otherwise
+    pub fn logica(&mut self, condition: &Cond, target: Label, jump_when: bool) -> SemanticResult {
+        match condition {
+            Cond::Or(operands) => self.cadena_logica(operands, target, jump_when, false),
+            Cond::And(operands) => self.cadena_logica(operands, target, jump_when, true),
+            Cond::Not(negated) => self.logica(negated, target, !jump_when),
+            Cond::Compare { left, op, right } => {
+                self.comparacion(left, op, right, target, jump_when)
             }
         }
     }
 
-    pub fn comparacion(&mut self, production: &Production) -> SemanticResult {
-        let operador_a = production_as_node(&production.items[0])?;
-        let op = production_as_leaf(&production.items[1])?;
-        let operador_b = production_as_node(&production.items[2])?;
-        Ok(format!(
-            "{} {} {}",
-            self.operador(operador_a)?,
-            op.lexeme,
-            self.operador(operador_b)?
-        ))
+    /// Emits one instruction per operation of the DAG, returning the operand that holds the
+    /// value of each node, indexed by its id. Children come before their parents, so the
+    /// operands of a node are always known. Leaves are used directly instead of being copied
+    /// to a temporary.
+    pub fn emit(&mut self, graph: &Graph) -> Result<(Code, Vec<Operand>), SemanticError> {
+        let mut operands: Vec<Operand> = Vec::with_capacity(graph.nodes.len());
+        let mut code = Code::new();
+        for node in &graph.nodes {
+            if node.is_leaf {
+                operands.push(match self.table.get(node.left) {
+                    Some(variable) if node.op == TokenType::Id => {
+                        Operand::Variable(variable.token.lexeme.clone())
+                    }
+                    _ => Operand::Constant(node.lexeme.clone()),
+                });
+                continue;
+            }
+            let op = BinaryOperator::from_lexeme(&node.lexeme).ok_or_else(|| {
+                SemanticError::from_unexpected(Token {
+                    token_type: node.op.clone(),
+                    lexeme: node.lexeme.clone(),
+                    ..Token::default()
+                })
+            })?;
+            let temp = self.next_temp();
+            code.push(Instruction::BinOp {
+                temp,
+                left: operands[node.left].clone(),
+                op,
+                right: operands[node.right].clone(),
+            });
+            operands.push(Operand::Temporary(temp));
+        }
+        Ok((code, operands))
     }
 
-    pub fn exp(&mut self, production: &Production) -> SemanticResult {
+    /// Translates the expression assigned to `id`
+    pub fn exp(
+        &mut self,
+        expr: &Expr,
+        id: &Token,
+    ) -> Result<(Code, Operand, VariableType), SemanticError> {
         let mut analyzer = ExpressionAnalyzer::from(&self.table);
-        analyzer.expresion_arit(production)?;
-        let graph = analyzer.graph;
-        let tags: Vec<String> = graph.stack.iter().map(|_| self.next_temp()).collect();
-        let instructions = graph
-            .stack
-            .iter()
-            .map(|hash| match graph.get(hash) {
-                Some(node_with_index) => match node_with_index.node.is_leaf {
-                    false => {
-                        let left = node_with_index.node.left;
-                        let right = node_with_index.node.right;
-                        if let (Some(left), Some(right)) = (graph.get(&left), graph.get(&right)) {
-                            let left_tag = &tags[left.index];
-                            let right_tag = &tags[right.index];
-                            return format!(
-                                "{} := {} {} {}\n",
-                                tags[node_with_index.index],
-                                left_tag,
-                                node_with_index.node.lexeme,
-                                right_tag
-                            );
-                        }
-                        String::new()
-                    }
-                    true => format!(
-                        "{} := {}\n",
-                        tags[node_with_index.index], node_with_index.node.lexeme
-                    ),
-                },
-                None => String::new(),
-            })
-            .collect::<Vec<String>>()
-            .join("");
-        Ok(instructions)
+        let root = analyzer.expresion(expr)?;
+        let expression_type = analyzer.expression_type(root);
+        let root = analyzer.simplify(&[root])?[0];
+        self.graphs.push((id.clone(), analyzer.graph.clone()));
+        let (code, operands) = self.emit(&analyzer.graph)?;
+        let operand = operands[root].clone();
+        Ok((code, operand, expression_type))
     }
 
-    pub fn asignar(&mut self, production: &Production) -> SemanticResult {
-        let id = &production.items[0];
-        let exp = &production.items[2];
-        let mut res = String::new();
-        if let (ProductionItem::Leaf(id), ProductionItem::Production(exp)) = (id, exp) {
-            res.push_str(&self.exp(&exp)?);
-            res.push_str(&format!(
-                "{} := {}",
-                append_id(&id.lexeme),
-                self.current_temp()
+    pub fn asignar(&mut self, id: &Token, expr: &Expr) -> SemanticResult {
+        let variable = match self.table.get_from_token(id) {
+            Some(variable) => variable.clone(),
+            None => return Err(SemanticError::from_undefined(id.clone())),
+        };
+        let (code, value, expression_type) = self.exp(expr, id)?;
+        if !variable.variable_type.accepts(&expression_type) {
+            return Err(SemanticError::from_narrowing(
+                id.clone(),
+                variable.variable_type,
+                expression_type,
             ));
         }
+        let mut res = code;
+        res.push(Instruction::Assign {
+            variable: id.lexeme.clone(),
+            value,
+        });
         Ok(res)
     }
 
-    pub fn bucle_while(&mut self, production: &Production) -> SemanticResult {
-        let condicion = &production.items[2];
-        let ordenes = &production.items[4];
-        let mut res = String::new();
-        if let (ProductionItem::Production(condicion), ProductionItem::Production(ordenes)) =
-            (condicion, ordenes)
-        {
-            let start_tag = self.next_jump();
-            let end_tag = self.next_jump();
-            res.push_str(&format!("{}:\n", start_tag));
-            res.push_str(&format!("if false "));
-            res.push_str(&self.comparacion(&condicion)?);
-            res.push_str(&format!(" jump to {}\n", end_tag));
-            res.push_str(&self.ordenes(&ordenes)?);
-            res.push_str(&format!("jump to {} \n", start_tag));
-            res.push_str(&format!("{}:\n", end_tag));
+    pub fn bucle_while(&mut self, condition: &Cond, body: &[Stmt]) -> SemanticResult {
+        let mut res = Code::new();
+        let start_tag = self.next_jump();
+        let end_tag = self.next_jump();
+        res.push(Instruction::Label(start_tag));
+        res.append(self.logica(condition, end_tag, false)?);
+        res.append(self.ordenes(body)?);
+        res.push(Instruction::Jump(start_tag));
+        res.push(Instruction::Label(end_tag));
+        Ok(res)
+    }
+
+    pub fn condicion(
+        &mut self,
+        condition: &Cond,
+        then_branch: &[Stmt],
+        else_branch: Option<&[Stmt]>,
+    ) -> SemanticResult {
+        let label = self.next_jump();
+        let mut res = Code::new();
+        res.append(self.logica(condition, label, false)?);
+        res.append(self.ordenes(then_branch)?);
+        match else_branch {
+            Some(else_branch) => {
+                let jump = self.next_jump();
+                res.push(Instruction::Jump(jump));
+                res.push(Instruction::Label(label));
+                res.append(self.ordenes(else_branch)?);
+                res.push(Instruction::Label(jump));
+            }
+            None => res.push(Instruction::Label(label)),
         }
         Ok(res)
     }
 
-    pub fn sig_condicion(&mut self, label: &str, production: &Production) -> SemanticResult {
-        if let ProductionItem::Leaf(token) = &production.items[0] {
-            return match token.token_type {
-                TokenType::End => Ok(format!("{}:\n", label)),
-                TokenType::Else => {
-                    let mut res = String::new();
-                    let jump = self.next_jump();
-                    res.push_str(&format!("jump to {}\n", jump));
-                    res.push_str(&format!("{}:\n", label));
-                    if let ProductionItem::Production(sig) = &production.items[1] {
-                        res.push_str(&self.ordenes(&sig)?);
-                    }
-                    res.push_str(&format!("{}:\n", jump));
-                    Ok(res)
-                }
-                _ => Ok(String::new()),
-            };
+    pub fn orden(&mut self, statement: &Stmt) -> SemanticResult {
+        match statement {
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => self.condicion(condition, then_branch, else_branch.as_deref()),
+            Stmt::While {
+                condition, body, ..
+            } => self.bucle_while(condition, body),
+            Stmt::Assign { target, value } => self.asignar(target, value),
+        }
+    }
+
+    pub fn ordenes(&mut self, statements: &[Stmt]) -> SemanticResult {
+        let mut parsed = Code::new();
+        for statement in statements {
+            parsed.append(self.orden(statement)?);
         }
-        Ok(String::new())
+        Ok(parsed)
     }
-    pub fn condicion(&mut self, production: &Production) -> SemanticResult {
-        let condicion = production_as_node(&production.items[2])?;
-        let ordenes = production_as_node(&production.items[4])?;
-        let sig_condicion = production_as_node(&production.items[5])?;
-        let jump = self.next_jump();
-        Ok(format!(
-            "if false {} jump to {}\n{}{}",
-            self.comparacion(condicion)?,
-            jump,
-            self.ordenes(ordenes)?,
-            self.sig_condicion(&jump, &sig_condicion)?,
-        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{error::SemanticErrorType, *};
+
+    /// Code and position of the errors of a program that does not compile
+    fn errors(source: &str) -> Vec<(&'static str, u32, u32)> {
+        match SemanticAnalyzer::new().parse_all(source) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .iter()
+                .map(|error| {
+                    let token = error.token().cloned().unwrap_or_default();
+                    (error.code(), token.line, token.col)
+                })
+                .collect(),
+        }
     }
 
-    pub fn orden(&mut self, production: &Production) -> SemanticResult {
-        let orden = production_as_node(&production.items[0])?;
-        let ordenes = match orden.production_type {
-            ProductionType::Condicion => self.condicion(orden),
-            ProductionType::BucleWhile => self.bucle_while(orden),
-            ProductionType::Asignar => self.asignar(orden),
-            _ => Ok(String::new()),
-        }?;
-        return Ok(format!("{}\n", ordenes));
+    #[test]
+    fn assigning_a_real_to_an_entero_is_an_error() {
+        assert_eq!(
+            errors("begin entero a; real r; a := r; end"),
+            vec![("E0203", 1, 25)]
+        );
+        assert_eq!(
+            errors("begin entero a;\n a := 1.5; end"),
+            vec![("E0203", 2, 2)]
+        );
+        assert_eq!(
+            errors("begin entero a; a := a / 2.0; end"),
+            vec![("E0203", 1, 17)]
+        );
     }
 
-    pub fn ordenes(&mut self, production: &Production) -> SemanticResult {
-        let mut parsed = String::new();
-        if production.items.len() == 3 {
-            let orden = production_as_node(&production.items[0])?;
-            let sig_ordenes = production_as_node(&production.items[2])?;
-            parsed.push_str(self.orden(&orden)?.as_str());
-            parsed.push_str(self.ordenes(&sig_ordenes)?.as_str());
+    #[test]
+    fn entero_values_widen_to_real() {
+        assert!(errors("begin entero a; real r; a := 1; r := a; r := a * 2 + 1.5; end").is_empty());
+    }
+
+    /// Code and variable name of the warnings of a program that compiles
+    fn warnings(source: &str) -> Vec<(&'static str, String)> {
+        let mut semantic = SemanticAnalyzer::new();
+        semantic
+            .parse_all(source)
+            .expect("the program should compile");
+        semantic
+            .warnings
+            .iter()
+            .map(|warning| (warning.code(), warning.token().lexeme.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn redeclaring_a_variable_points_at_both_declarations() {
+        let source = "begin entero a;\n real b, a; a := 1; end";
+        assert_eq!(errors(source), vec![("E0204", 2, 10)]);
+        let error = SemanticAnalyzer::new()
+            .parse_all(source)
+            .expect_err("the program should not compile")
+            .remove(0);
+        match error.error_type() {
+            SemanticErrorType::Redeclared(_, previous) => {
+                assert_eq!((previous.line, previous.col), (1, 14))
+            }
+            other => panic!("expected a redeclaration, found {:?}", other),
         }
-        Ok(parsed)
+    }
+
+    #[test]
+    fn warns_about_variables_that_are_not_used_both_ways() {
+        assert_eq!(
+            warnings("begin entero a, b, c, d; a := b; c := 1; d := 2; d := d + 1; end"),
+            vec![
+                ("W0002", String::from("a")),
+                ("W0003", String::from("b")),
+                ("W0002", String::from("c"))
+            ]
+        );
+        assert_eq!(
+            warnings("begin entero a; real r; r := 1.5; r := r * 2; end"),
+            vec![("W0001", String::from("a"))]
+        );
+    }
+
+    #[test]
+    fn undeclared_names_are_errors() {
+        assert_eq!(
+            errors("begin entero a; z := 1; end"),
+            vec![("E0200", 1, 17)]
+        );
+        assert_eq!(
+            errors("begin entero a; a := z + 1; end"),
+            vec![("E0200", 1, 22)]
+        );
+        assert_eq!(
+            errors("begin entero a;\n if (a > 0 and not (b < 1)) a := 1; end; end"),
+            vec![("E0200", 2, 21)]
+        );
+        assert_eq!(
+            errors("begin entero a; while (a < y) a := a + 1; endwhile; end"),
+            vec![("E0200", 1, 28)]
+        );
+    }
+
+    #[test]
+    fn or_jumps_to_the_body_as_soon_as_an_operand_holds() {
+        let code = SemanticAnalyzer::new()
+            .parse_all("begin entero a, b, c; if (a < 1 or b < 2) c := 1; end; end")
+            .expect("the program should compile");
+        let lines: Vec<String> = code
+            .instructions
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "if false _a >= 1 jump to jmp_2",
+                "if false _b < 2 jump to jmp_1",
+                "jmp_2:",
+                "_c := 1",
+                "jmp_1:"
+            ]
+        );
+    }
+
+    #[test]
+    fn positions_use_the_configured_tab_width() {
+        let source = "begin\n\tentero a;\n\tz := 1;\nend";
+        assert_eq!(errors(source), vec![("E0200", 3, 5)]);
+        let error = SemanticAnalyzer::with_tab_width(8)
+            .parse_all(source)
+            .expect_err("the program should not compile")
+            .remove(0);
+        let token = error.token().cloned().unwrap_or_default();
+        assert_eq!((token.line, token.col), (3, 9));
     }
 }
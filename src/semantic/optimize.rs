@@ -0,0 +1,399 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{symbols::VariableType, token::TokenType};
+
+use super::{
+    graph::{Graph, Node, NodeId},
+    ir::{BinaryOperator, Code, Instruction, Operand},
+};
+
+/// Value of a number known at compile time
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Constant {
+    Entero(i64),
+    Real(f64),
+}
+
+impl Constant {
+    /// Value of a number leaf, `None` for variables, operations and literals out of range
+    fn from_node(node: &Node) -> Option<Self> {
+        match (node.is_leaf, &node.op) {
+            (true, TokenType::Entero) => node.lexeme.parse().ok().map(Constant::Entero),
+            (true, TokenType::Real) => node.lexeme.parse().ok().map(Constant::Real),
+            _ => None,
+        }
+    }
+
+    fn as_real(&self) -> f64 {
+        match self {
+            Constant::Entero(value) => *value as f64,
+            Constant::Real(value) => *value,
+        }
+    }
+
+    fn is(&self, value: i64) -> bool {
+        self.as_real() == value as f64
+    }
+
+    /// Evaluates the operation the same way the interpreter does, `None` when it would fail
+    /// at runtime or overflow a real
+    fn apply(self, op: &BinaryOperator, right: Self) -> Option<Self> {
+        if let (Constant::Entero(left), Constant::Entero(right)) = (self, right) {
+            return match op {
+                BinaryOperator::Add => Some(left.wrapping_add(right)),
+                BinaryOperator::Sub => Some(left.wrapping_sub(right)),
+                BinaryOperator::Mul => Some(left.wrapping_mul(right)),
+                BinaryOperator::Div => left.checked_div(right),
+            }
+            .map(Constant::Entero);
+        }
+        let (left, right) = (self.as_real(), right.as_real());
+        let value = match op {
+            BinaryOperator::Add => left + right,
+            BinaryOperator::Sub => left - right,
+            BinaryOperator::Mul => left * right,
+            BinaryOperator::Div => left / right,
+        };
+        value.is_finite().then_some(Constant::Real(value))
+    }
+
+    fn to_node(self) -> Node {
+        match self {
+            Constant::Entero(value) => {
+                Node::from_constant(&value.to_string(), VariableType::Entero)
+            }
+            Constant::Real(value) => {
+                // Reals keep a decimal point so they are not read back as enteros
+                let mut lexeme = format!("{:?}", value);
+                if !lexeme.contains('.') {
+                    let exponent = lexeme.find('e').unwrap_or(lexeme.len());
+                    lexeme.insert_str(exponent, ".0");
+                }
+                Node::from_constant(&lexeme, VariableType::Real)
+            }
+        }
+    }
+
+    fn zero(variable_type: &VariableType) -> Self {
+        match variable_type {
+            VariableType::Entero => Constant::Entero(0),
+            VariableType::Real => Constant::Real(0.0),
+        }
+    }
+}
+
+impl Graph {
+    /// Node an operation reduces to, `Ok(None)` when it has to be computed at runtime and
+    /// `Err(())` when it divides by a constant zero
+    fn reduce(&self, node: &Node, left: NodeId, right: NodeId) -> Result<Option<Node>, ()> {
+        let (left_node, right_node) = match (self.get(left), self.get(right)) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Ok(None),
+        };
+        let op = match BinaryOperator::from_lexeme(&node.lexeme) {
+            Some(op) => op,
+            None => return Ok(None),
+        };
+        let (left_value, right_value) = (
+            Constant::from_node(left_node),
+            Constant::from_node(right_node),
+        );
+        if op == BinaryOperator::Div && right_value.is_some_and(|value| value.is(0)) {
+            return Err(());
+        }
+        if let (Some(left_value), Some(right_value)) = (left_value, right_value) {
+            return Ok(left_value.apply(&op, right_value).map(Constant::to_node));
+        }
+        // An identity only drops the operation when the remaining operand has the same type,
+        // otherwise an entero would stop being widened to real
+        let keeps = |kept: &Node| (kept.node_type == node.node_type).then(|| kept.clone());
+        let is_left = |value: i64| left_value.is_some_and(|constant| constant.is(value));
+        let is_right = |value: i64| right_value.is_some_and(|constant| constant.is(value));
+        Ok(match op {
+            BinaryOperator::Add if is_right(0) => keeps(left_node),
+            BinaryOperator::Add if is_left(0) => keeps(right_node),
+            BinaryOperator::Sub if is_right(0) => keeps(left_node),
+            BinaryOperator::Mul if is_left(0) || is_right(0) => {
+                Some(Constant::zero(&node.node_type).to_node())
+            }
+            BinaryOperator::Mul if is_right(1) => keeps(left_node),
+            BinaryOperator::Mul if is_left(1) => keeps(right_node),
+            BinaryOperator::Div if is_right(1) => keeps(left_node),
+            _ => None,
+        })
+    }
+
+    /// Folds operations on constants and applies the identities `x + 0`, `x - 0`, `x * 1`,
+    /// `x / 1` and `x * 0`. Returns the simplified graph with the nodes `roots` became, or the
+    /// id of the first division by a constant zero.
+    pub fn simplify(&self, roots: &[NodeId]) -> Result<(Graph, Vec<NodeId>), NodeId> {
+        let mut simplified = Graph::new();
+        // Children come before their parents, so their replacement is always known
+        let mut replaced: Vec<NodeId> = Vec::with_capacity(self.nodes.len());
+        for (id, node) in self.nodes.iter().enumerate() {
+            if node.is_leaf {
+                replaced.push(simplified.add(node.clone()));
+                continue;
+            }
+            let (left, right) = (replaced[node.left], replaced[node.right]);
+            let reduced = match simplified.reduce(node, left, right) {
+                Ok(reduced) => reduced,
+                Err(()) => return Err(id),
+            };
+            let node = reduced.unwrap_or(Node {
+                left,
+                right,
+                ..node.clone()
+            });
+            replaced.push(simplified.add(node));
+        }
+        let roots: Vec<NodeId> = roots.iter().map(|root| replaced[*root]).collect();
+        let (graph, renumbered) = simplified.reachable(&roots);
+        Ok((graph, roots.iter().map(|root| renumbered[*root]).collect()))
+    }
+
+    /// Copy of the graph without the nodes that no root depends on, along with the new id of
+    /// each node that was kept
+    pub fn reachable(&self, roots: &[NodeId]) -> (Graph, Vec<NodeId>) {
+        let mut used = HashSet::new();
+        let mut pending = roots.to_vec();
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.get(id) {
+                if used.insert(id) && !node.is_leaf {
+                    pending.push(node.left);
+                    pending.push(node.right);
+                }
+            }
+        }
+        let mut graph = Graph::new();
+        let mut renumbered = vec![0; self.nodes.len()];
+        for (id, node) in self.nodes.iter().enumerate() {
+            if !used.contains(&id) {
+                continue;
+            }
+            let node = match node.is_leaf {
+                true => node.clone(),
+                false => Node {
+                    left: renumbered[node.left],
+                    right: renumbered[node.right],
+                    ..node.clone()
+                },
+            };
+            renumbered[id] = graph.add(node);
+        }
+        (graph, renumbered)
+    }
+}
+
+/// Local value numbering: inside a basic block an operation that was already computed reuses
+/// the temporary holding it, until one of its variables is assigned again. Temporaries are
+/// renumbered in order afterwards, so the dropped ones leave no gaps.
+pub fn value_numbering(code: &Code) -> Code {
+    let mut result = Code::new();
+    let mut available: HashMap<(Operand, BinaryOperator, Operand), u32> = HashMap::new();
+    let mut renamed: HashMap<u32, u32> = HashMap::new();
+    let mut next_temp = 0;
+    let rename = |operand: &Operand, renamed: &HashMap<u32, u32>| match operand {
+        Operand::Temporary(temp) => Operand::Temporary(renamed.get(temp).copied().unwrap_or(*temp)),
+        _ => operand.clone(),
+    };
+    for instruction in &code.instructions {
+        match instruction {
+            Instruction::BinOp {
+                temp,
+                left,
+                op,
+                right,
+            } => {
+                let (left, right) = (rename(left, &renamed), rename(right, &renamed));
+                let commutative = matches!(op, BinaryOperator::Add | BinaryOperator::Mul);
+                let existing = available
+                    .get(&(left.clone(), *op, right.clone()))
+                    .or_else(|| match commutative {
+                        true => available.get(&(right.clone(), *op, left.clone())),
+                        false => None,
+                    });
+                if let Some(existing) = existing {
+                    renamed.insert(*temp, *existing);
+                    continue;
+                }
+                next_temp += 1;
+                renamed.insert(*temp, next_temp);
+                available.insert((left.clone(), *op, right.clone()), next_temp);
+                result.push(Instruction::BinOp {
+                    temp: next_temp,
+                    left,
+                    op: *op,
+                    right,
+                });
+            }
+            Instruction::Assign { variable, value } => {
+                result.push(Instruction::Assign {
+                    variable: variable.clone(),
+                    value: rename(value, &renamed),
+                });
+                let written = Operand::Variable(variable.clone());
+                available.retain(|(left, _, right), _| *left != written && *right != written);
+            }
+            Instruction::CondJumpFalse {
+                left,
+                op,
+                right,
+                target,
+            } => {
+                result.push(Instruction::CondJumpFalse {
+                    left: rename(left, &renamed),
+                    op: *op,
+                    right: rename(right, &renamed),
+                    target: *target,
+                });
+                available.clear();
+            }
+            // Labels start a new basic block and jumps end the current one
+            Instruction::Label(_) | Instruction::Jump(_) => {
+                result.push(instruction.clone());
+                available.clear();
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::semantic::{
+        ir::{BinaryOperator, Code, Instruction, Label, Operand},
+        SemanticAnalyzer,
+    };
+
+    use super::value_numbering;
+
+    fn ir(source: &str) -> Vec<String> {
+        let code = SemanticAnalyzer::new()
+            .parse_all(source)
+            .expect("the program should compile");
+        code.instructions
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect()
+    }
+
+    fn error_codes(source: &str) -> Vec<&'static str> {
+        match SemanticAnalyzer::new().parse_all(source) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|error| error.code()).collect(),
+        }
+    }
+
+    #[test]
+    fn folds_operations_on_constants() {
+        assert_eq!(
+            ir("begin entero a; real r; a := 2 * 3 + 4; a := 7 / 2; r := 7.0 / 2; end"),
+            vec!["_a := 10", "_a := 3", "_r := 3.5"]
+        );
+    }
+
+    #[test]
+    fn multiplying_by_zero_keeps_the_type_of_the_operation() {
+        assert_eq!(
+            ir("begin entero a; real r; a := a * 0; r := r * 0; r := 0 * r; end"),
+            vec!["_a := 0", "_r := 0.0", "_r := 0.0"]
+        );
+    }
+
+    #[test]
+    fn adding_zero_is_dropped_only_when_the_type_is_kept() {
+        assert_eq!(
+            ir("begin entero a; real r; a := a + 0; r := r + 0; r := a + 0.0; end"),
+            vec![
+                "_a := _a",
+                "_r := _r",
+                "__temp_1 := _a + 0.0",
+                "_r := __temp_1"
+            ]
+        );
+    }
+
+    #[test]
+    fn dividing_by_a_constant_zero_is_an_error() {
+        assert_eq!(
+            error_codes("begin entero a; a := a / 0; end"),
+            vec!["E0205"]
+        );
+        assert_eq!(
+            error_codes("begin real r; r := 1.5 / (3 - 3); end"),
+            vec!["E0205"]
+        );
+        assert!(error_codes("begin entero a; a := a / 1; end").is_empty());
+    }
+
+    const DECLARATIONS: &str = "begin entero a, b, c, d, e;";
+
+    #[test]
+    fn reuses_an_operation_computed_in_the_same_block() {
+        assert_eq!(
+            ir(&format!(
+                "{} a := b + c; d := c + b; e := b - c; e := c - b; end",
+                DECLARATIONS
+            )),
+            vec![
+                "__temp_1 := _b + _c",
+                "_a := __temp_1",
+                "_d := __temp_1",
+                "__temp_2 := _b - _c",
+                "_e := __temp_2",
+                "__temp_3 := _c - _b",
+                "_e := __temp_3"
+            ]
+        );
+    }
+
+    #[test]
+    fn assigning_a_variable_forgets_the_operations_that_read_it() {
+        assert_eq!(
+            ir(&format!(
+                "{} a := b + c; b := 1; d := b + c; end",
+                DECLARATIONS
+            )),
+            vec![
+                "__temp_1 := _b + _c",
+                "_a := __temp_1",
+                "_b := 1",
+                "__temp_2 := _b + _c",
+                "_d := __temp_2"
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_start_a_new_block() {
+        assert_eq!(
+            ir(&format!(
+                "{} a := b + c; while (a < 1) d := b + c; endwhile; end",
+                DECLARATIONS
+            )),
+            vec![
+                "__temp_1 := _b + _c",
+                "_a := __temp_1",
+                "jmp_1:",
+                "if false _a < 1 jump to jmp_2",
+                "__temp_2 := _b + _c",
+                "_d := __temp_2",
+                "jump to jmp_1",
+                "jmp_2:"
+            ]
+        );
+    }
+
+    #[test]
+    fn jumps_end_the_block() {
+        let sum = |temp| Instruction::BinOp {
+            temp,
+            left: Operand::Variable(String::from("b")),
+            op: BinaryOperator::Add,
+            right: Operand::Variable(String::from("c")),
+        };
+        let code = Code::from(vec![sum(1), Instruction::Jump(Label(1)), sum(2)]);
+        assert_eq!(value_numbering(&code), code);
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashSet;
+
+use crate::{
+    ast::{Cond, Expr, Stmt},
+    symbols::SymbolsTable,
+};
+
+use super::warning::SemanticWarning;
+
+/// Tracks which identifiers are read and which are assigned by the statements of a program
+#[derive(Debug, Clone, Default)]
+pub struct UsageAnalyzer {
+    pub read: HashSet<String>,
+    pub assigned: HashSet<String>,
+}
+
+impl UsageAnalyzer {
+    pub fn new() -> Self {
+        UsageAnalyzer {
+            read: HashSet::new(),
+            assigned: HashSet::new(),
+        }
+    }
+
+    pub fn walk(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            match statement {
+                Stmt::Assign { target, value } => {
+                    self.assigned.insert(target.lexeme.clone());
+                    self.expression(value);
+                }
+                Stmt::If {
+                    condition,
+                    then_branch,
+                    else_branch,
+                    ..
+                } => {
+                    self.condition(condition);
+                    self.walk(then_branch);
+                    if let Some(else_branch) = else_branch {
+                        self.walk(else_branch);
+                    }
+                }
+                Stmt::While {
+                    condition, body, ..
+                } => {
+                    self.condition(condition);
+                    self.walk(body);
+                }
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Cond) {
+        match condition {
+            Cond::Compare { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Cond::And(operands) | Cond::Or(operands) => {
+                operands.iter().for_each(|operand| self.condition(operand))
+            }
+            Cond::Not(negated) => self.condition(negated),
+        }
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Var(token) => {
+                self.read.insert(token.lexeme.clone());
+            }
+            Expr::Num(_) => {}
+            Expr::Bin { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+        }
+    }
+
+    /// Warnings for the declared variables, in declaration order
+    pub fn warnings(&self, table: &SymbolsTable) -> Vec<SemanticWarning> {
+        table
+            .variables
+            .iter()
+            .filter_map(|variable| {
+                let token = variable.token.clone();
+                let read = self.read.contains(&token.lexeme);
+                let assigned = self.assigned.contains(&token.lexeme);
+                match (read, assigned) {
+                    (false, false) => Some(SemanticWarning::from_unused(token)),
+                    (false, true) => Some(SemanticWarning::from_never_read(token)),
+                    (true, false) => Some(SemanticWarning::from_never_assigned(token)),
+                    (true, true) => None,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+
+use crate::{
+    messages::{format, Locale},
+    token::Token,
+};
+
+#[derive(Debug, Clone)]
+pub enum SemanticWarningType {
+    Unused(Token),
+    NeverRead(Token),
+    NeverAssigned(Token),
+}
+
+#[derive(Debug, Clone)]
+pub struct SemanticWarning {
+    pub warning_type: SemanticWarningType,
+}
+
+impl SemanticWarning {
+    pub fn from_unused(token: Token) -> Self {
+        SemanticWarning {
+            warning_type: SemanticWarningType::Unused(token),
+        }
+    }
+
+    pub fn from_never_read(token: Token) -> Self {
+        SemanticWarning {
+            warning_type: SemanticWarningType::NeverRead(token),
+        }
+    }
+
+    pub fn from_never_assigned(token: Token) -> Self {
+        SemanticWarning {
+            warning_type: SemanticWarningType::NeverAssigned(token),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.warning_type {
+            SemanticWarningType::Unused(_) => "W0001",
+            SemanticWarningType::NeverRead(_) => "W0002",
+            SemanticWarningType::NeverAssigned(_) => "W0003",
+        }
+    }
+
+    pub fn token(&self) -> &Token {
+        match &self.warning_type {
+            SemanticWarningType::Unused(token)
+            | SemanticWarningType::NeverRead(token)
+            | SemanticWarningType::NeverAssigned(token) => token,
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        let token = self.token();
+        format(
+            self.code(),
+            locale,
+            &[&token.lexeme, &token.line, &token.col],
+        )
+    }
+}
+
+impl fmt::Display for SemanticWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let locale = Locale::default();
+        write!(
+            f,
+            "{}",
+            format("warning-prefix", locale, &[&self.message(locale)])
+        )
+    }
+}
@@ -1,29 +1,81 @@
 use std::{error, fmt};
 
-use crate::token::Token;
+use crate::{
+    messages::{format, text, Locale},
+    token::{token_to_str, token_type_to_str, Token, TokenType},
+};
+
+/// What the parser was looking for when it found the unexpected token
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    Token(TokenType),
+    Number,
+    Statement,
+}
+
+impl Expected {
+    pub fn to_str(&self, locale: Locale) -> String {
+        match self {
+            Expected::Token(token_type) => token_type_to_str(token_type, locale),
+            Expected::Number => text("expected-number", locale).to_string(),
+            Expected::Statement => text("expected-statement", locale).to_string(),
+        }
+    }
+}
+
+impl Default for Expected {
+    fn default() -> Self {
+        Expected::Token(TokenType::default())
+    }
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct SintacticError {
     token: Token,
-    expected: String,
+    expected: Expected,
 }
 impl SintacticError {
-    pub fn new(token: &Token, expected: &str) -> Self {
+    pub fn new(token: &Token, expected: Expected) -> Self {
         SintacticError {
-            expected: String::from(expected),
+            expected,
             token: token.clone(),
         }
     }
+
+    pub fn token(&self) -> &Token {
+        &self.token
+    }
+
+    pub fn expected(&self) -> &Expected {
+        &self.expected
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self.expected {
+            Expected::Token(_) => "E0100",
+            Expected::Number => "E0101",
+            Expected::Statement => "E0102",
+        }
+    }
+
+    pub fn message(&self, locale: Locale) -> String {
+        format(
+            self.code(),
+            locale,
+            &[
+                &token_to_str(&self.token, locale),
+                &self.token.line,
+                &self.token.col,
+                &self.expected.to_str(locale),
+            ],
+        )
+    }
 }
 impl error::Error for SintacticError {}
 //impl From< for SintacticError{
 //}
 impl fmt::Display for SintacticError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Caracter inesperado '{}', en la linea {} columna {}, se esperaba: '{}'",
-            self.token.lexeme, self.token.line, self.token.col, self.expected
-        )
+        write!(f, "{}", self.message(Locale::default()))
     }
 }
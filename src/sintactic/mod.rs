@@ -1,41 +1,55 @@
 pub mod error;
 
 use crate::{
-    lexic::LexicAnalyzer,
+    lexic::{LexicAnalyzer, DEFAULT_TAB_WIDTH},
     production::{Production, ProductionType},
-    token::{token_type_to_str, Token, TokenType},
+    token::{Token, TokenType},
 };
 
-use self::error::SintacticError;
+use self::error::{Expected, SintacticError};
 
 pub type ItermediateRep = Production;
 pub type SintacticResult = Result<ItermediateRep, SintacticError>;
 
 pub struct SintacticAnalyzer<'a> {
     pub lexic: LexicAnalyzer<'a>,
+    pub errors: Vec<SintacticError>,
     last_token: Token,
+    depth: usize,
 }
 
 impl<'a> SintacticAnalyzer<'a> {
     pub fn new(input: &'a str) -> Self {
+        SintacticAnalyzer::with_tab_width(input, DEFAULT_TAB_WIDTH)
+    }
+
+    /// Parser whose token columns treat a tab as advancing to the next multiple of `tab_width`
+    pub fn with_tab_width(input: &'a str, tab_width: usize) -> Self {
         SintacticAnalyzer {
-            lexic: LexicAnalyzer::new(input),
+            lexic: LexicAnalyzer::with_tab_width(input, tab_width),
+            errors: Vec::new(),
             last_token: Token::default(),
+            depth: 0,
         }
     }
 
     pub fn next_token(&mut self) {
+        match self.last_token.token_type {
+            TokenType::ParentesisAbierto => self.depth += 1,
+            TokenType::ParentesisCerrado => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
         self.last_token = self.lexic.next_token();
     }
 
     pub fn is_last(&self, token_type: &TokenType) -> Result<(), SintacticError> {
         if *token_type != self.last_token.token_type {
-            return Err(SintacticError::new(
+            Err(SintacticError::new(
                 &self.last_token,
-                &token_type_to_str(&token_type),
-            ));
+                Expected::Token(token_type.clone()),
+            ))
         } else {
-            return Ok(());
+            Ok(())
         }
     }
 
@@ -50,9 +64,57 @@ impl<'a> SintacticAnalyzer<'a> {
         Ok(())
     }
 
+    /// Records an error, ignoring it when another error was already reported at the same token
+    pub fn report(&mut self, error: SintacticError) {
+        if let Some(last) = self.errors.last() {
+            if last.token().line == error.token().line && last.token().col == error.token().col {
+                return;
+            }
+        }
+        self.errors.push(error);
+    }
+
+    pub fn is_sync(&self) -> bool {
+        matches!(
+            self.last_token.token_type,
+            TokenType::Semicolon
+                | TokenType::End
+                | TokenType::Endwhile
+                | TokenType::Else
+                | TokenType::EOF
+        )
+    }
+
+    pub fn is_block_end(&self) -> bool {
+        self.is_sync() && self.last_token.token_type != TokenType::Semicolon
+    }
+
+    /// Panic-mode recovery: records the error and skips tokens until `;`, `end`, `endwhile`,
+    /// `else` or the end of the input. The skipped tokens are kept in an error production.
+    pub fn synchronize(&mut self, error: SintacticError) -> Production {
+        self.report(error);
+        let mut prod = Production::new(ProductionType::Error);
+        while !self.is_sync() {
+            prod.push_leaf(self.last_token.clone());
+            self.next_token();
+        }
+        prod
+    }
+
+    /// Pushes the `;` that closes a declaration or statement, recovering when it is missing
+    pub fn push_semicolon(&mut self, production: &mut Production, recovered: bool) {
+        if let Err(error) = self.push_token_if(&TokenType::Semicolon, production) {
+            if recovered && self.is_block_end() {
+                return;
+            }
+            production.push_node(self.synchronize(error));
+            let _ = self.push_token_if(&TokenType::Semicolon, production);
+        }
+    }
+
     pub fn sig_lista_variables(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::SigListaVariables);
-        while let Ok(_) = self.push_token_if(&TokenType::Coma, &mut prod) {
+        while self.push_token_if(&TokenType::Coma, &mut prod).is_ok() {
             prod.push_node(self.lista_variables()?);
         }
         Ok(prod)
@@ -72,11 +134,25 @@ impl<'a> SintacticAnalyzer<'a> {
         Ok(prod)
     }
 
+    /// Parses a declaration followed by its `;`, synchronizing on errors
+    pub fn declaracion_recover(&mut self, prod: &mut Production) {
+        let recovered = match self.declaracion() {
+            Ok(declaracion) => {
+                prod.push_node(declaracion);
+                false
+            }
+            Err(error) => {
+                prod.push_node(self.synchronize(error));
+                true
+            }
+        };
+        self.push_semicolon(prod, recovered);
+    }
+
     pub fn sig_declaraciones(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::SigDeclaraciones);
         if let TokenType::Tipo = self.last_token.token_type {
-            prod.push_node(self.declaracion()?);
-            self.push_token_if(&TokenType::Semicolon, &mut prod)?;
+            self.declaracion_recover(&mut prod);
             prod.push_node(self.sig_declaraciones()?);
         }
         Ok(prod)
@@ -84,17 +160,30 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn declaraciones(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Declaraciones);
-        prod.push_node(self.declaracion()?);
-        self.push_token_if(&TokenType::Semicolon, &mut prod)?;
+        self.declaracion_recover(&mut prod);
         prod.push_node(self.sig_declaraciones()?);
         Ok(prod)
     }
 
+    /// Parses a statement followed by its `;`, synchronizing on errors
+    pub fn orden_recover(&mut self, prod: &mut Production) {
+        let recovered = match self.orden() {
+            Ok(orden) => {
+                prod.push_node(orden);
+                false
+            }
+            Err(error) => {
+                prod.push_node(self.synchronize(error));
+                true
+            }
+        };
+        self.push_semicolon(prod, recovered);
+    }
+
     pub fn sig_ordenes(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::SigOrdenes);
-        if self.is_orden() {
-            prod.push_node(self.orden()?);
-            self.push_token_if(&TokenType::Semicolon, &mut prod)?;
+        if !self.is_block_end() {
+            self.orden_recover(&mut prod);
             prod.push_node(self.sig_ordenes()?);
         }
         Ok(prod)
@@ -102,11 +191,11 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn sig_condicion(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::SigCondicion);
-        if let Ok(_) = self.push_token_if(&TokenType::Else, &mut prod) {
+        if self.push_token_if(&TokenType::Else, &mut prod).is_ok() {
             prod.push_node(self.ordenes()?);
         }
         self.push_token_if(&TokenType::End, &mut prod)?;
-        return Ok(prod);
+        Ok(prod)
     }
 
     pub fn numeros(&mut self) -> SintacticResult {
@@ -116,15 +205,12 @@ impl<'a> SintacticAnalyzer<'a> {
             self.next_token();
             return Ok(prod);
         }
-        Err(SintacticError::new(
-            &self.last_token,
-            "número entero o real",
-        ))
+        Err(SintacticError::new(&self.last_token, Expected::Number))
     }
 
     pub fn operador(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Operador);
-        if let Ok(_) = self.push_token_if(&TokenType::Id, &mut prod) {
+        if self.push_token_if(&TokenType::Id, &mut prod).is_ok() {
             return Ok(prod);
         }
         prod.push_node(self.numeros()?);
@@ -133,18 +219,119 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn comparacion(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Comparacion);
-        prod.push_node(self.operador()?);
+        prod.push_node(self.expresion_arit()?);
         self.push_token_if(&TokenType::OperadorCondicion, &mut prod)?;
-        prod.push_node(self.operador()?);
+        prod.push_node(self.expresion_arit()?);
+        Ok(prod)
+    }
+
+    pub fn agrupacion_logica(&mut self, prod: &mut Production) -> Result<(), SintacticError> {
+        self.push_token_if(&TokenType::ParentesisAbierto, prod)?;
+        prod.push_node(self.expresion_logica()?);
+        self.push_token_if(&TokenType::ParentesisCerrado, prod)
+    }
+
+    pub fn factor_logico(&mut self) -> SintacticResult {
+        let mut prod = Production::new(ProductionType::FactorLogico);
+        if self.push_token_if(&TokenType::Not, &mut prod).is_ok() {
+            prod.push_node(self.factor_logico()?);
+            return Ok(prod);
+        }
+        if self.last_token.token_type != TokenType::ParentesisAbierto {
+            prod.push_node(self.comparacion()?);
+            return Ok(prod);
+        }
+        // A parenthesis may open either a logical group or an arithmetic factor such as
+        // `(a + 1) < b`, so try the logical group first and backtrack when it fails
+        let lexic = self.lexic.clone();
+        let last_token = self.last_token.clone();
+        let depth = self.depth;
+        let group_error = match self.agrupacion_logica(&mut prod) {
+            Ok(_) => return Ok(prod),
+            Err(error) => error,
+        };
+        self.lexic = lexic;
+        self.last_token = last_token;
+        self.depth = depth;
+        let mut prod = Production::new(ProductionType::FactorLogico);
+        match self.comparacion() {
+            Ok(comparacion) => {
+                prod.push_node(comparacion);
+                Ok(prod)
+            }
+            Err(error) => {
+                let group = (group_error.token().line, group_error.token().col);
+                if group > (error.token().line, error.token().col) {
+                    return Err(group_error);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    pub fn rest_termino_logico(&mut self) -> SintacticResult {
+        let mut prod = Production::new(ProductionType::RestTerminoLogico);
+        if self.push_token_if(&TokenType::And, &mut prod).is_ok() {
+            prod.push_node(self.factor_logico()?);
+            prod.push_node(self.rest_termino_logico()?);
+        }
+        Ok(prod)
+    }
+
+    pub fn termino_logico(&mut self) -> SintacticResult {
+        let mut prod = Production::new(ProductionType::TerminoLogico);
+        prod.push_node(self.factor_logico()?);
+        prod.push_node(self.rest_termino_logico()?);
+        Ok(prod)
+    }
+
+    pub fn rest_logica(&mut self) -> SintacticResult {
+        let mut prod = Production::new(ProductionType::RestLogica);
+        if self.push_token_if(&TokenType::Or, &mut prod).is_ok() {
+            prod.push_node(self.termino_logico()?);
+            prod.push_node(self.rest_logica()?);
+        }
+        Ok(prod)
+    }
+
+    pub fn expresion_logica(&mut self) -> SintacticResult {
+        let mut prod = Production::new(ProductionType::ExpresionLogica);
+        prod.push_node(self.termino_logico()?);
+        prod.push_node(self.rest_logica()?);
         Ok(prod)
     }
 
+    pub fn encabezado(&mut self, prod: &mut Production) -> Result<(), SintacticError> {
+        self.push_token_if(&TokenType::ParentesisAbierto, prod)?;
+        prod.push_node(self.expresion_logica()?);
+        self.push_token_if(&TokenType::ParentesisCerrado, prod)
+    }
+
+    /// Whether the current token is the `)` that closes a group opened at `depth`
+    pub fn is_closing(&self, depth: usize) -> bool {
+        self.last_token.token_type == TokenType::ParentesisCerrado && self.depth <= depth + 1
+    }
+
+    /// Parses the parenthesized condition of an `if` or `while`. On errors it skips to the
+    /// closing parenthesis so the body of the block is still analyzed.
+    pub fn encabezado_recover(&mut self, prod: &mut Production) {
+        let depth = self.depth;
+        if let Err(error) = self.encabezado(prod) {
+            self.report(error);
+            let mut skipped = Production::new(ProductionType::Error);
+            while !self.is_sync() && !self.is_closing(depth) {
+                skipped.push_leaf(self.last_token.clone());
+                self.next_token();
+            }
+            prod.push_node(skipped);
+            let _ = self.push_token_if(&TokenType::ParentesisCerrado, prod);
+        }
+    }
+
     pub fn condicion(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Condicion);
         self.push_token_if(&TokenType::If, &mut prod)?;
-        self.push_token_if(&TokenType::ParentesisAbierto, &mut prod)?;
-        prod.push_node(self.comparacion()?);
-        self.push_token_if(&TokenType::ParentesisCerrado, &mut prod)?;
+        self.encabezado_recover(&mut prod);
         prod.push_node(self.ordenes()?);
         prod.push_node(self.sig_condicion()?);
         Ok(prod)
@@ -153,9 +340,7 @@ impl<'a> SintacticAnalyzer<'a> {
     pub fn bucle_while(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::BucleWhile);
         self.push_token_if(&TokenType::While, &mut prod)?;
-        self.push_token_if(&TokenType::ParentesisAbierto, &mut prod)?;
-        prod.push_node(self.comparacion()?);
-        self.push_token_if(&TokenType::ParentesisCerrado, &mut prod)?;
+        self.encabezado_recover(&mut prod);
         prod.push_node(self.ordenes()?);
         self.push_token_if(&TokenType::Endwhile, &mut prod)?;
         Ok(prod)
@@ -163,7 +348,10 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn factor(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Factor);
-        if let Ok(_) = self.push_token_if(&TokenType::ParentesisAbierto, &mut prod) {
+        if self
+            .push_token_if(&TokenType::ParentesisAbierto, &mut prod)
+            .is_ok()
+        {
             prod.push_node(self.expresion_arit()?);
             self.push_token_if(&TokenType::ParentesisCerrado, &mut prod)?;
             return Ok(prod);
@@ -174,7 +362,10 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn rest_term(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::RestTerm);
-        if let Ok(_) = self.push_token_if(&TokenType::OperadorAritB, &mut prod) {
+        if self
+            .push_token_if(&TokenType::OperadorAritB, &mut prod)
+            .is_ok()
+        {
             prod.push_node(self.factor()?);
             prod.push_node(self.rest_term()?);
         }
@@ -190,7 +381,10 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn rest_expr(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::RestExp);
-        if let Ok(_) = self.push_token_if(&TokenType::OperadorAritA, &mut prod) {
+        if self
+            .push_token_if(&TokenType::OperadorAritA, &mut prod)
+            .is_ok()
+        {
             prod.push_node(self.termino()?);
             prod.push_node(self.rest_expr()?);
         }
@@ -213,12 +407,10 @@ impl<'a> SintacticAnalyzer<'a> {
     }
 
     pub fn is_orden(&mut self) -> bool {
-        match self.last_token.token_type {
-            TokenType::If => true,
-            TokenType::While => true,
-            TokenType::Id => true,
-            _ => false,
-        }
+        matches!(
+            self.last_token.token_type,
+            TokenType::If | TokenType::While | TokenType::Id
+        )
     }
 
     pub fn orden(&mut self) -> SintacticResult {
@@ -226,10 +418,7 @@ impl<'a> SintacticAnalyzer<'a> {
             TokenType::If => self.condicion(),
             TokenType::While => self.bucle_while(),
             TokenType::Id => self.asignar(),
-            _ => Err(SintacticError::new(
-                &self.last_token,
-                "if, while o una asignación",
-            )),
+            _ => Err(SintacticError::new(&self.last_token, Expected::Statement)),
         }?;
         let mut prod = Production::new(ProductionType::Orden);
         prod.push_node(content);
@@ -238,25 +427,159 @@ impl<'a> SintacticAnalyzer<'a> {
 
     pub fn ordenes(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Ordenes);
-        prod.push_node(self.orden()?);
-        self.push_token_if(&TokenType::Semicolon, &mut prod)?;
+        self.orden_recover(&mut prod);
         prod.push_node(self.sig_ordenes()?);
         Ok(prod)
     }
 
     pub fn programa(&mut self) -> SintacticResult {
         let mut prod = Production::new(ProductionType::Programa);
-        self.push_token_if(&TokenType::Begin, &mut prod)?;
+        if let Err(error) = self.push_token_if(&TokenType::Begin, &mut prod) {
+            self.report(error);
+        }
         prod.push_node(self.declaraciones()?);
-        prod.push_node(self.ordenes()?);
-        self.push_token_if(&TokenType::End, &mut prod)?;
+        let mut ordenes = self.ordenes()?;
+        while let TokenType::Else | TokenType::Endwhile = self.last_token.token_type {
+            // A block closer without its opening statement, skip it and keep going
+            self.report(SintacticError::new(
+                &self.last_token,
+                Expected::Token(TokenType::End),
+            ));
+            let mut stray = Production::new(ProductionType::Error);
+            stray.push_leaf(self.last_token.clone());
+            self.next_token();
+            ordenes.push_node(stray);
+            // The `;` of a stray `endwhile;` is optional, an `else` is followed by statements
+            let _ = self.push_token_if(&TokenType::Semicolon, &mut ordenes);
+            ordenes.push_node(self.sig_ordenes()?);
+        }
+        prod.push_node(ordenes);
+        if let Err(error) = self.push_token_if(&TokenType::End, &mut prod) {
+            self.report(error);
+        }
         Ok(prod)
     }
 
-    pub fn analize(&'a mut self) -> SintacticResult {
+    /// Parses the whole input, collecting every error instead of stopping at the first one.
+    /// The returned tree is partial when errors were found.
+    pub fn analize_all(&mut self) -> (Production, Vec<SintacticError>) {
+        self.errors.clear();
         self.next_token();
-        let production = self.programa()?;
-        self.is_last(&TokenType::EOF)?;
-        Ok(production)
+        let production = match self.programa() {
+            Ok(production) => production,
+            Err(error) => {
+                self.report(error);
+                Production::new(ProductionType::Programa)
+            }
+        };
+        if let Err(error) = self.is_last(&TokenType::EOF) {
+            self.report(error);
+        }
+        (production, self.errors.clone())
+    }
+
+    pub fn analize(&'a mut self) -> SintacticResult {
+        let (production, errors) = self.analize_all();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(production),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::production::ProductionItem;
+
+    use super::*;
+
+    const FIVE_ERRORS: &str = "begin
+    entero a b;
+    real c;
+    a := ;
+    c := 1.5;
+    if (a > ) a := 1; end;
+    while (a < 3) a := a + 1 endwhile;
+    else
+    c := 2.0;
+end";
+
+    fn first_leaf(prod: &Production) -> Option<&Token> {
+        prod.items.iter().find_map(|item| match item {
+            ProductionItem::Leaf(token) => Some(token),
+            ProductionItem::Production(child) => first_leaf(child),
+        })
+    }
+
+    /// First token and line of every declaration and statement left in the tree
+    fn statements(prod: &Production, found: &mut Vec<(String, u32)>) {
+        if let ProductionType::Declaracion | ProductionType::Orden = prod.production_type {
+            if let Some(token) = first_leaf(prod) {
+                found.push((token.lexeme.clone(), token.line));
+            }
+        }
+        for item in &prod.items {
+            if let ProductionItem::Production(child) = item {
+                statements(child, found);
+            }
+        }
+    }
+
+    #[test]
+    fn reports_every_error_of_the_program() {
+        let (_, errors) = SintacticAnalyzer::new(FIVE_ERRORS).analize_all();
+        let errors: Vec<(u32, u32, &str)> = errors
+            .iter()
+            .map(|error| (error.token().line, error.token().col, error.code()))
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                (2, 14, "E0100"),
+                (4, 10, "E0101"),
+                (6, 13, "E0101"),
+                (7, 30, "E0100"),
+                (8, 5, "E0100")
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_code_after_each_error_in_the_tree() {
+        let (tree, _) = SintacticAnalyzer::new(FIVE_ERRORS).analize_all();
+        let mut found = Vec::new();
+        statements(&tree, &mut found);
+        let expected = [
+            ("real", 3),
+            ("c", 5),
+            ("if", 6),
+            ("a", 6),
+            ("while", 7),
+            ("c", 9),
+        ];
+        for (lexeme, line) in expected {
+            assert!(
+                found.contains(&(lexeme.to_string(), line)),
+                "missing {} at line {} in {:?}",
+                lexeme,
+                line,
+                found
+            );
+        }
+    }
+
+    #[test]
+    fn a_stray_closer_does_not_cascade() {
+        for source in [
+            "begin entero a; a := 1; endwhile a := 2; end",
+            "begin entero a; a := 1; endwhile; a := 2; end",
+        ] {
+            let (_, errors) = SintacticAnalyzer::new(source).analize_all();
+            let positions: Vec<(u32, u32)> = errors
+                .iter()
+                .map(|error| (error.token().line, error.token().col))
+                .collect();
+            assert_eq!(positions, vec![(1, 25)], "{}", source);
+        }
     }
 }
@@ -1,25 +1,43 @@
-use std::{
-    collections::{hash_map::DefaultHasher, HashMap},
-    hash::{Hash, Hasher},
-};
+use std::collections::HashMap;
 
-use crate::token::Token;
+use crate::{
+    messages::{text, Locale},
+    token::Token,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum VariableType {
     Entero,
     Real,
 }
 
+impl VariableType {
+    /// Type of a binary operation, widening entero to real when the operands differ
+    pub fn widen(left: &VariableType, right: &VariableType) -> VariableType {
+        match (left, right) {
+            (VariableType::Entero, VariableType::Entero) => VariableType::Entero,
+            _ => VariableType::Real,
+        }
+    }
+
+    /// Whether a value of type `from` can be stored in a variable of this type
+    pub fn accepts(&self, from: &VariableType) -> bool {
+        !matches!((self, from), (VariableType::Entero, VariableType::Real))
+    }
+}
+
+/// Position of a variable in the symbols table, in declaration order
+pub type VariableId = usize;
+
 #[derive(Debug, Clone)]
 pub struct Variable {
     pub token: Token,
-    pub index: usize,
+    pub index: VariableId,
     pub variable_type: VariableType,
 }
 
 impl Variable {
-    pub fn new(token: Token, index: usize, variable_type: VariableType) -> Self {
+    pub fn new(token: Token, index: VariableId, variable_type: VariableType) -> Self {
         Variable {
             token,
             index,
@@ -28,52 +46,54 @@ impl Variable {
     }
 }
 
+/// Declared variables, looked up by comparing their names so two of them never share an id
 #[derive(Debug, Clone, Default)]
 pub struct SymbolsTable {
-    pub table: HashMap<u64, Variable>,
-    pub stack: Vec<u64>,
+    pub variables: Vec<Variable>,
+    pub ids: HashMap<String, VariableId>,
 }
 
 impl SymbolsTable {
     pub fn new() -> Self {
         SymbolsTable {
-            table: HashMap::new(),
-            stack: Vec::new(),
+            variables: Vec::new(),
+            ids: HashMap::new(),
         }
     }
-    pub fn add(&mut self, token: &Token, variable_type: &VariableType) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        token.hash(&mut hasher);
-        let hash = hasher.finish();
-        if let None = self.table.get(&hash) {
-            let token_with_index =
-                Variable::new(token.clone(), self.stack.len(), variable_type.clone());
-            self.table.insert(hash, token_with_index);
-            self.stack.push(hash);
+    /// Declares a variable, on a redeclaration the previous declaration is returned as error
+    pub fn add(
+        &mut self,
+        token: &Token,
+        variable_type: &VariableType,
+    ) -> Result<VariableId, Variable> {
+        if let Some(previous) = self.get_from_token(token) {
+            return Err(previous.clone());
         }
-        hash
+        let id = self.variables.len();
+        self.variables
+            .push(Variable::new(token.clone(), id, variable_type.clone()));
+        self.ids.insert(token.lexeme.clone(), id);
+        Ok(id)
     }
 
     pub fn clear(&mut self) {
-        self.table.clear();
-        self.stack.clear();
+        self.variables.clear();
+        self.ids.clear();
     }
 
-    pub fn get(&self, hash: &u64) -> Option<&Variable> {
-        self.table.get(hash)
+    pub fn get(&self, id: VariableId) -> Option<&Variable> {
+        self.variables.get(id)
     }
 
     pub fn get_from_token(&self, token: &Token) -> Option<&Variable> {
-        let mut hasher = DefaultHasher::new();
-        token.hash(&mut hasher);
-        let hash = hasher.finish();
-        self.table.get(&hash)
+        self.ids.get(&token.lexeme).and_then(|id| self.get(*id))
     }
+}
 
-    pub fn get_hash_if_set(&self, token: &Token) -> Option<u64> {
-        match self.get_from_token(token) {
-            Some(variable) => Some(self.stack[variable.index]),
-            None => None,
-        }
+pub fn variable_type_to_str(variable_type: &VariableType, locale: Locale) -> String {
+    match variable_type {
+        VariableType::Entero => text("variable-type-entero", locale),
+        VariableType::Real => text("variable-type-real", locale),
     }
+    .to_string()
 }
@@ -1,6 +1,6 @@
-use std::hash::Hash;
+use crate::messages::{text, Locale};
 
-#[derive(Debug, Clone, PartialEq, Default, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
 pub enum TokenType {
     Tipo,
     Begin,
@@ -21,6 +21,9 @@ pub enum TokenType {
     OperadorAsig,
     While,
     Endwhile,
+    And,
+    Or,
+    Not,
     EOF,
     #[default]
     Unknown,
@@ -32,38 +35,97 @@ pub struct Token {
     pub lexeme: String,
     pub line: u32,
     pub col: u32,
+    /// Byte offset of the first character in the source
+    pub offset: usize,
 }
 
-impl Hash for Token {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.lexeme.hash(state);
-        self.token_type.hash(state);
-    }
+/// Comment skipped by the lexer, kept as trivia so the formatter can write it back
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// Whole comment, including `//` or the braces
+    pub text: String,
+    pub line: u32,
+    pub col: u32,
+    pub offset: usize,
+    /// Type of the token that comes right after the comment
+    pub following: TokenType,
 }
 
-pub fn token_type_to_str(token_type: &TokenType) -> String {
+pub fn token_type_to_str(token_type: &TokenType, locale: Locale) -> String {
     match token_type {
-        TokenType::Tipo => "tipo",
+        TokenType::Tipo => text("token-tipo", locale),
         TokenType::Begin => "begin",
         TokenType::End => "end",
-        TokenType::Id => "Id",
-        TokenType::Entero => "entero",
-        TokenType::Real => "real",
+        TokenType::Id => text("token-id", locale),
+        TokenType::Entero => text("token-entero", locale),
+        TokenType::Real => text("token-real", locale),
         TokenType::Coma => ",",
         TokenType::Punto => ".",
         TokenType::Semicolon => ";",
         TokenType::If => "if",
-        TokenType::ParentesisAbierto => ")",
+        TokenType::ParentesisAbierto => "(",
         TokenType::ParentesisCerrado => ")",
         TokenType::Else => "else",
-        TokenType::OperadorAritA => "+ o -",
-        TokenType::OperadorAritB => "/ o *",
-        TokenType::OperadorCondicion => "operador condicional",
+        TokenType::OperadorAritA => text("token-operador-arit-a", locale),
+        TokenType::OperadorAritB => text("token-operador-arit-b", locale),
+        TokenType::OperadorCondicion => text("token-operador-condicion", locale),
         TokenType::OperadorAsig => ":=",
         TokenType::While => "while",
         TokenType::Endwhile => "endwhile",
-        TokenType::EOF => "EOF",
-        TokenType::Unknown => "No reconocido",
+        TokenType::And => "and",
+        TokenType::Or => "or",
+        TokenType::Not => "not",
+        TokenType::EOF => text("token-eof", locale),
+        TokenType::Unknown => text("token-unknown", locale),
     }
     .to_string()
 }
+
+/// Text of a token as shown in messages, the end of the input has no printable lexeme
+pub fn token_to_str(token: &Token, locale: Locale) -> String {
+    match token.token_type {
+        TokenType::EOF => text("token-eof", locale).to_string(),
+        _ => token.lexeme.clone(),
+    }
+}
+
+/// Aligned table with the type, lexeme, line and column of each token
+pub fn tokens_table(tokens: &[Token], locale: Locale) -> String {
+    let header = [
+        "column-type",
+        "column-lexeme",
+        "column-line",
+        "column-column",
+    ]
+    .map(|key| text(key, locale).to_string());
+    let rows: Vec<[String; 4]> = tokens
+        .iter()
+        .map(|token| {
+            [
+                token_type_to_str(&token.token_type, locale),
+                token.lexeme.clone(),
+                token.line.to_string(),
+                token.col.to_string(),
+            ]
+        })
+        .collect();
+    let mut widths = header.clone().map(|title| title.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let format_row = |row: &[String; 4]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+    let separator = widths.map(|width| "-".repeat(width)).join("-+-");
+    let mut lines = vec![format_row(&header), separator];
+    lines.extend(rows.iter().map(format_row));
+    lines.join("\n")
+}